@@ -1,10 +1,43 @@
 //! Command Line argument parser.
 
-use std::path::PathBuf;
+#[cfg(feature = "serde")]
+extern crate serde_json;
+
+#[cfg(feature = "log")]
+#[macro_use]
+extern crate log;
+
+#[cfg(feature = "toml")]
+extern crate toml;
+
+use std::path::{Path, PathBuf};
 use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::env;
+use std::ffi::OsString;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::io::{BufRead, IsTerminal, Read, Write};
+use std::num::{IntErrorKind, ParseIntError};
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Emits a trace-level log message when the `log` feature is enabled.
+#[cfg(feature = "log")]
+macro_rules! trace_parse {
+    ($($arg:tt)*) => { trace!($($arg)*) };
+}
+
+/// Compiled out when the `log` feature is disabled.
+#[cfg(not(feature = "log"))]
+macro_rules! trace_parse {
+    ($($arg:tt)*) => {};
+}
 
 /// List of parameter types that can be processed.
+#[derive(Debug, Clone, PartialEq)]
 pub enum ParameterType {
     /// Flag parameter.
     Flag,
@@ -14,9 +47,40 @@ pub enum ParameterType {
 
     /// File Path.
     Path,
+
+    /// Text Value.
+    String,
+
+    /// i64 Value.
+    Integer,
+
+    /// f64 Value. Infinite and NaN values are rejected.
+    Float,
+
+    /// List of File Paths. Each use of the parameter adds a path.
+    PathList,
+
+    /// List of Text Values. Each use of the parameter adds a value.
+    StringList,
+
+    /// Path to an existing file. Stored as `ParameterValue::Path`.
+    ExistingPath,
+
+    /// Path to an existing directory. Stored as `ParameterValue::Path`.
+    ExistingDir,
+
+    /// Boolean Value. Accepts `true`/`false`, `yes`/`no` and `1`/`0`, ignoring case.
+    Bool,
+
+    /// Flag parameter that counts how many times it is used.
+    Counter,
+
+    /// u64 Value, for values too large for `UInteger` such as byte counts.
+    ULong,
 }
 
 /// `ParameterType` with its assigned value.
+#[derive(Debug, Clone, PartialEq)]
 pub enum ParameterValue {
     /// No value.
     None,
@@ -29,158 +93,5409 @@ pub enum ParameterValue {
 
     /// File Path.
     Path(PathBuf),
+
+    /// Text Value.
+    String(String),
+
+    /// i64 Value.
+    Integer(i64),
+
+    /// f64 Value.
+    Float(f64),
+
+    /// List of File Paths.
+    PathList(Vec<PathBuf>),
+
+    /// List of Text Values.
+    StringList(Vec<String>),
+
+    /// Boolean Value.
+    Bool(bool),
+
+    /// Number of times a counter parameter has been used.
+    Counter(u32),
+
+    /// u64 Value.
+    ULong(u64),
 }
 
-struct Parameter {
-    pub parameter_name: String,
-    pub parameter_type: ParameterType,
-    pub aliases: Vec<String>,
-    value: ParameterValue,
+impl From<u32> for ParameterValue {
+    fn from(value: u32) -> ParameterValue {
+        ParameterValue::UInteger(value)
+    }
 }
 
-/// Command Line Processor
-pub struct CommandLineProcessor {
-    parameters: HashMap<String, Parameter>,
-    help_text: Option<String>,
-    version_text: Option<String>,
-    abort_flag: bool,
+impl From<u64> for ParameterValue {
+    fn from(value: u64) -> ParameterValue {
+        ParameterValue::ULong(value)
+    }
 }
 
-impl CommandLineProcessor {
-    /// Returns a new `CommandLineProcessor`.
-    pub fn new() -> CommandLineProcessor {
-        CommandLineProcessor {
-            parameters: HashMap::new(),
-            help_text: None,
-            version_text: None,
-            abort_flag: false,
+impl From<i32> for ParameterValue {
+    fn from(value: i32) -> ParameterValue {
+        ParameterValue::Integer(i64::from(value))
+    }
+}
+
+impl From<i64> for ParameterValue {
+    fn from(value: i64) -> ParameterValue {
+        ParameterValue::Integer(value)
+    }
+}
+
+impl From<f64> for ParameterValue {
+    fn from(value: f64) -> ParameterValue {
+        ParameterValue::Float(value)
+    }
+}
+
+impl From<bool> for ParameterValue {
+    fn from(value: bool) -> ParameterValue {
+        ParameterValue::Bool(value)
+    }
+}
+
+impl From<String> for ParameterValue {
+    fn from(value: String) -> ParameterValue {
+        ParameterValue::String(value)
+    }
+}
+
+impl<'a> From<&'a str> for ParameterValue {
+    fn from(value: &'a str) -> ParameterValue {
+        ParameterValue::String(value.to_owned())
+    }
+}
+
+impl From<PathBuf> for ParameterValue {
+    fn from(value: PathBuf) -> ParameterValue {
+        ParameterValue::Path(value)
+    }
+}
+
+/// How the `CommandLineProcessor` handles unknown parameters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParseMode {
+    /// Unknown parameters are collected, set the abort flag and cause parsing to return an error.
+    Strict,
+
+    /// Unknown parameters are collected and otherwise ignored. This includes single-dash arguments such as `-Wall`
+    /// that contain a character that is not a short flag.
+    Permissive,
+}
+
+/// How path expansion handles a `$VAR` reference to an environment variable that is not set.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnsetVariablePolicy {
+    /// Parsing returns `ParseErrorKind::UnsetVariable`.
+    Error,
+
+    /// The reference is replaced with an empty string.
+    Empty,
+}
+
+/// Kinds of errors that can occur while parsing the command line.
+#[derive(Debug)]
+pub enum ParseErrorKind {
+    /// The parameter requires a value but none was provided.
+    MissingValue {
+        /// Name of the parameter.
+        parameter: String,
+    },
+
+    /// The value for the parameter could not be converted to an unsigned integer.
+    InvalidUInteger {
+        /// Name of the parameter.
+        parameter: String,
+
+        /// Error returned by the conversion.
+        source: ParseIntError,
+    },
+
+    /// The value for the parameter could not be converted to a signed integer.
+    InvalidInteger {
+        /// Name of the parameter.
+        parameter: String,
+
+        /// Error returned by the conversion.
+        source: ParseIntError,
+    },
+
+    /// The value for the parameter is not a finite floating-point number.
+    InvalidFloat {
+        /// Name of the parameter.
+        parameter: String,
+
+        /// Value that failed to convert.
+        value: String,
+    },
+
+    /// The value for the parameter is not a recognized boolean value.
+    InvalidBool {
+        /// Name of the parameter.
+        parameter: String,
+
+        /// Value that failed to convert.
+        value: String,
+    },
+
+    /// The path for the parameter does not exist.
+    PathNotFound {
+        /// Name of the parameter.
+        parameter: String,
+
+        /// Path that was provided.
+        path: PathBuf,
+    },
+
+    /// The path for the parameter exists but is not a file.
+    NotAFile {
+        /// Name of the parameter.
+        parameter: String,
+
+        /// Path that was provided.
+        path: PathBuf,
+    },
+
+    /// The path for the parameter exists but is not a directory.
+    NotADirectory {
+        /// Name of the parameter.
+        parameter: String,
+
+        /// Path that was provided.
+        path: PathBuf,
+    },
+
+    /// The path for the parameter must be absolute but is relative.
+    PathNotAbsolute {
+        /// Name of the parameter.
+        parameter: String,
+
+        /// Path that was provided.
+        path: PathBuf,
+    },
+
+    /// The path for the parameter must be relative but is absolute.
+    PathNotRelative {
+        /// Name of the parameter.
+        parameter: String,
+
+        /// Path that was provided.
+        path: PathBuf,
+    },
+
+    /// A path for the parameter refers to an environment variable that is not set.
+    UnsetVariable {
+        /// Name of the parameter.
+        parameter: String,
+
+        /// Name of the environment variable.
+        variable: String,
+    },
+
+    /// The value for a parameter with several types does not match any of them.
+    NoMatchingType {
+        /// Name of the parameter.
+        parameter: String,
+
+        /// Value that was provided.
+        value: String,
+    },
+
+    /// The value for the parameter is not one of the allowed choices.
+    InvalidChoice {
+        /// Name of the parameter.
+        parameter: String,
+
+        /// Value that was provided.
+        value: String,
+
+        /// Allowed choices.
+        choices: Vec<String>,
+    },
+
+    /// The value for the parameter is larger than the parameter's type can hold.
+    ValueTooLarge {
+        /// Name of the parameter.
+        parameter: String,
+
+        /// Value that was provided.
+        value: String,
+
+        /// Largest allowed value.
+        max: u64,
+    },
+
+    /// The unsigned integer value for the parameter is outside the allowed range.
+    OutOfRange {
+        /// Name of the parameter.
+        parameter: String,
+
+        /// Value that was provided.
+        value: u32,
+
+        /// Smallest allowed value.
+        min: u32,
+
+        /// Largest allowed value.
+        max: u32,
+    },
+
+    /// The parameter's validator rejected the value.
+    ValidationFailed {
+        /// Name of the parameter.
+        parameter: String,
+
+        /// Message returned by the validator.
+        message: String,
+    },
+
+    /// A value was provided for a flag, which does not take one.
+    UnexpectedValue {
+        /// Alias of the flag.
+        flag: String,
+    },
+
+    /// The argument does not match any registered parameter.
+    UnknownParameter {
+        /// The unknown argument.
+        argument: String,
+    },
+
+    /// The abbreviated argument matches more than one parameter.
+    AmbiguousParameter {
+        /// The abbreviated argument.
+        argument: String,
+
+        /// Aliases of the parameters it matches.
+        candidates: Vec<String>,
+    },
+
+    /// A combined short flag argument such as `-abc` contains a character that is not a flag.
+    InvalidShortFlag {
+        /// The combined argument.
+        argument: String,
+
+        /// The offending character.
+        flag: char,
+    },
+
+    /// There are more positional arguments than have been added.
+    UnexpectedPositional {
+        /// The extra argument.
+        argument: String,
+    },
+
+    /// The value of a parameter's fallback environment variable could not be parsed.
+    InvalidEnvValue {
+        /// Name of the environment variable.
+        variable: String,
+
+        /// Error returned when parsing the value.
+        source: Box<ParseError>,
+    },
+
+    /// A parameter that takes a fixed number of values was followed by fewer values.
+    NotEnoughValues {
+        /// Name of the parameter.
+        parameter: String,
+
+        /// Number of values the parameter takes.
+        expected: usize,
+
+        /// Number of values that were provided.
+        found: usize,
+    },
+
+    /// A response file given as `@path` could not be read.
+    ResponseFileFailed {
+        /// Path of the response file.
+        path: PathBuf,
+
+        /// Error returned when reading the file.
+        source: io::Error,
+    },
+
+    /// Response files refer to each other more than 10 levels deep.
+    ResponseFileTooDeep {
+        /// Path of the response file that was not read.
+        path: PathBuf,
+    },
+
+    /// The config file given to the config parameter could not be loaded.
+    ConfigFileFailed {
+        /// Path of the config file.
+        path: PathBuf,
+
+        /// Error returned when loading the file.
+        source: Box<ConfigError>,
+    },
+
+    /// The value of a parameter could not be read from standard input.
+    StdinFailed {
+        /// Name of the parameter.
+        parameter: String,
+
+        /// Error returned when reading standard input.
+        source: io::Error,
+    },
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseErrorKind::MissingValue { parameter } => write!(f, "No value passed for parameter {}", parameter),
+            ParseErrorKind::InvalidUInteger { parameter, source } => write!(f, "Unable to convert parameter {} to unsigned integer: {}", parameter, source),
+            ParseErrorKind::InvalidInteger { parameter, source } => write!(f, "Unable to convert parameter {} to signed integer: {}", parameter, source),
+            ParseErrorKind::InvalidFloat { parameter, value } => write!(f, "Unable to convert parameter {} to floating-point number: {}", parameter, value),
+            ParseErrorKind::InvalidBool { parameter, value } => write!(f, "Unable to convert parameter {} to boolean: {}", parameter, value),
+            ParseErrorKind::PathNotFound { parameter, path } => write!(f, "Path {} for parameter {} does not exist", path.display(), parameter),
+            ParseErrorKind::NotAFile { parameter, path } => write!(f, "Path {} for parameter {} is not a file", path.display(), parameter),
+            ParseErrorKind::NotADirectory { parameter, path } => write!(f, "Path {} for parameter {} is not a directory", path.display(), parameter),
+            ParseErrorKind::PathNotAbsolute { parameter, path } => write!(f, "Path {} for parameter {} must be absolute", path.display(), parameter),
+            ParseErrorKind::PathNotRelative { parameter, path } => write!(f, "Path {} for parameter {} must be relative", path.display(), parameter),
+            ParseErrorKind::UnsetVariable { parameter, variable } => write!(f, "Environment variable {} in parameter {} is not set", variable, parameter),
+            ParseErrorKind::NoMatchingType { parameter, value } => write!(f, "Value {} for parameter {} does not match any of its types", value, parameter),
+            ParseErrorKind::InvalidChoice { parameter, value, choices } => write!(f, "Invalid value {} for parameter {}. Valid choices are: {}", value, parameter, choices.join(", ")),
+            ParseErrorKind::ValueTooLarge { parameter, value, max } => write!(f, "Value {} for parameter {} is too large, the maximum is {}", value, parameter, max),
+            ParseErrorKind::OutOfRange { parameter, value, min, max } => write!(f, "Value {} for parameter {} must be between {} and {}", value, parameter, min, max),
+            ParseErrorKind::ValidationFailed { parameter, message } => write!(f, "Invalid value for parameter {}: {}", parameter, message),
+            ParseErrorKind::UnexpectedValue { flag } => write!(f, "Flag {} does not take a value", flag),
+            ParseErrorKind::UnknownParameter { argument } => write!(f, "Unknown parameter: {}", argument),
+            ParseErrorKind::AmbiguousParameter { argument, candidates } => write!(f, "Ambiguous parameter {} could be any of: {}", argument, candidates.join(", ")),
+            ParseErrorKind::InvalidShortFlag { argument, flag } => write!(f, "Combined flags {} contain -{}, which is not a flag", argument, flag),
+            ParseErrorKind::UnexpectedPositional { argument } => write!(f, "Unexpected positional argument: {}", argument),
+            ParseErrorKind::InvalidEnvValue { variable, source } => write!(f, "Invalid value in environment variable {}: {}", variable, source),
+            ParseErrorKind::NotEnoughValues { parameter, expected, found } => write!(f, "Parameter {} takes {} values, found {}", parameter, expected, found),
+            ParseErrorKind::ResponseFileFailed { path, source } => write!(f, "Unable to read response file {}: {}", path.display(), source),
+            ParseErrorKind::ConfigFileFailed { path, source } => write!(f, "Unable to load config file {}: {}", path.display(), source),
+            ParseErrorKind::ResponseFileTooDeep { path } => write!(f, "Response file {} is nested too deeply", path.display()),
+            ParseErrorKind::StdinFailed { parameter, source } => write!(f, "Unable to read parameter {} from standard input: {}", parameter, source),
         }
     }
+}
+
+/// Error returned when parsing the command line, made of the kind of error and the position of the argument that
+/// caused it.
+#[derive(Debug)]
+pub struct ParseError {
+    kind: ParseErrorKind,
+    index: Option<usize>,
+}
+
+impl ParseError {
+    fn new(kind: ParseErrorKind, index: Option<usize>) -> ParseError {
+        ParseError { kind, index }
+    }
+
+    /// Returns the kind of error.
+    pub fn kind(&self) -> &ParseErrorKind {
+        &self.kind
+    }
+
+    /// Returns the zero-based index of the argument that caused the error, or `None` if the error did not come from
+    /// a command line argument. For an invalid value this is the argument holding the value.
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    /// Sets the index of an error that does not have one yet.
+    fn at_index(mut self, argument_index: usize) -> ParseError {
+        self.index = self.index.or(Some(argument_index));
+        self
+    }
+
+    /// Adds an offset to the index, used for errors from a subcommand's arguments.
+    fn offset_index(mut self, offset: usize) -> ParseError {
+        self.index = self.index.map(|index| index + offset);
+        self
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.kind.fmt(f)
+    }
+}
+
+impl Error for ParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.kind {
+            ParseErrorKind::InvalidUInteger { source, .. } => Some(source),
+            ParseErrorKind::InvalidInteger { source, .. } => Some(source),
+            ParseErrorKind::InvalidEnvValue { source, .. } => Some(source.as_ref()),
+            ParseErrorKind::StdinFailed { source, .. } => Some(source),
+            ParseErrorKind::ResponseFileFailed { source, .. } => Some(source),
+            ParseErrorKind::ConfigFileFailed { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// Function that checks a parsed value, returning an error message if the value is not allowed.
+pub type Validator = dyn Fn(&ParameterValue) -> Result<(), String> + Send + Sync;
+
+/// Function that runs as soon as a flag is parsed, such as printing a list for `--list-plugins`.
+pub type Callback = dyn Fn() + Send + Sync;
+
+#[derive(Clone)]
+struct Parameter {
+    pub parameter_name: String,
+    pub parameter_type: ParameterType,
+    pub aliases: Vec<String>,
+    description: Option<String>,
+    value_name: Option<String>,
+    required: bool,
+    range: Option<(u32, u32)>,
+    allow_size_suffix: bool,
+    choices: Option<Vec<String>>,
+    choices_case_insensitive: bool,
+    union_types: Vec<ParameterType>,
+    keywords: Vec<String>,
+    negatable: bool,
+    delimiter: Option<char>,
+    keep_empty_values: bool,
+    allow_stdin: bool,
+    stdin_disabled: bool,
+    require_absolute: bool,
+    require_relative: bool,
+    expand_path: Option<UnsetVariablePolicy>,
+    group: Option<String>,
+    hidden: bool,
+    secret: bool,
+    deprecated: Option<String>,
+    nargs: Option<usize>,
+    greedy: bool,
+    env_var: Option<String>,
+    validator: Option<Arc<Validator>>,
+    on_set: Option<(Arc<Callback>, bool)>,
+    default: ParameterValue,
+    file_value: ParameterValue,
+    config_value: ParameterValue,
+    value: ParameterValue,
+    was_set: bool,
+    prompted: bool,
+}
 
-    /// Add a parameter to be parsed.
-    pub fn add_parameter(&mut self, parameter_name: &str, parameter_type: ParameterType, aliases: Vec<String>) {
-        let parameter = Parameter {
+impl Parameter {
+    fn new(parameter_name: &str, parameter_type: ParameterType, aliases: Vec<String>) -> Parameter {
+        Parameter {
             parameter_name: parameter_name.to_owned(),
             parameter_type,
             aliases,
+            description: None,
+            value_name: None,
+            required: false,
+            range: None,
+            allow_size_suffix: false,
+            choices: None,
+            choices_case_insensitive: false,
+            union_types: Vec::new(),
+            keywords: Vec::new(),
+            negatable: false,
+            delimiter: None,
+            keep_empty_values: false,
+            allow_stdin: false,
+            stdin_disabled: false,
+            require_absolute: false,
+            require_relative: false,
+            expand_path: None,
+            group: None,
+            hidden: false,
+            secret: false,
+            deprecated: None,
+            nargs: None,
+            greedy: false,
+            env_var: None,
+            validator: None,
+            on_set: None,
+            default: ParameterValue::None,
+            file_value: ParameterValue::None,
+            config_value: ParameterValue::None,
             value: ParameterValue::None,
+            was_set: false,
+            prompted: false,
+        }
+    }
+
+    /// Returns the parsed value, or the value from a config file or the default value if the parameter has not been set.
+    /// A value from the config file named by the config parameter overrides a value from `load_from_file`.
+    fn current_value(&self) -> &ParameterValue {
+        match (&self.value, &self.config_value, &self.file_value) {
+            (ParameterValue::None, ParameterValue::None, ParameterValue::None) => &self.default,
+            (ParameterValue::None, ParameterValue::None, file_value) => file_value,
+            (ParameterValue::None, config_value, _) => config_value,
+            (value, _, _) => value,
+        }
+    }
+
+    /// Returns the value placeholder shown in help text for the parameter.
+    fn placeholder(&self) -> Option<String> {
+        let placeholder = match (&self.value_name, &self.choices) {
+            (Some(value_name), _) if self.takes_value() => format!("<{}>", value_name),
+            _ if !self.union_types.is_empty() => {
+                let names: Vec<String> = self.union_types.iter()
+                    .filter_map(type_placeholder)
+                    .map(|placeholder| placeholder.trim_matches(['<', '>']).to_owned())
+                    .chain(self.keywords.iter().cloned())
+                    .collect();
+                format!("<{}>", names.join("|"))
+            },
+            (_, Some(choices)) => format!("<{}>", choices.join("|")),
+            _ => type_placeholder(&self.parameter_type)?.to_owned(),
         };
 
-        self.parameters.insert(parameter_name.to_owned(), parameter);
+        match self.nargs {
+            Some(count) => Some(vec![placeholder; count].join(" ")),
+            None if self.greedy => Some(format!("{}...", placeholder)),
+            None => Some(placeholder),
+        }
     }
 
-    /// Parses the program's command line parameters.
-    /// 
-    /// # Panics
-    /// Panics if the parameter type requires a value and no value is provided.
-    /// It will also panic if the parameter is the wrong type.
-    pub fn parse_command_line(&mut self) {
-        let mut iter = env::args();
-        iter.next(); // Skip executable name
-
-        loop {
-            match iter.next() {
-                Some(argument) => {
-                    match argument.as_ref() {
-                        "--help" => {
-                            self.print_help_text();
-                            self.abort_flag = true;
-                        },
-                        "--h" => {
-                            self.print_help_text();
-                            self.abort_flag = true;
-                        },
-                        "--version" => {
-                            self.print_version_text();
-                            self.abort_flag = true;
-                        },
-                        "--v" => {
-                            self.print_version_text();
-                            self.abort_flag = true;
-                        },
-                        arg => {
-                            let mut parameter_exists = false;
-
-                            for (name, parameter) in self.parameters.iter_mut() {
-                                if parameter.aliases.iter().any(|x| x == arg) {
-                                    parameter_exists = true;
-
-                                    match parameter.parameter_type {
-                                        ParameterType::Flag => parameter.value = ParameterValue::Flag,
-                                        ParameterType::UInteger => {
-                                            match iter.next() {
-                                                Some(val) => {
-                                                    match val.parse::<u32>() {
-                                                        Ok(val) => parameter.value = ParameterValue::UInteger(val),
-                                                        Err(err) => panic!(format!("Unable to convert parameter {} to unsigned integer\n{}", name, err))
-                                                    }
-                                                    
-                                                },
-                                                None => panic!(format!("No value passed for parameter {}", name)),
-                                            }
-                                        },
-                                        ParameterType::Path => {
-                                            match iter.next() {
-                                                Some(val) => {
-                                                    let mut path = PathBuf::new();
-                                                    path.push(val);
-                                                    parameter.value = ParameterValue::Path(path);
-                                                },
-                                                None => panic!(format!("No value passed for parameter {}", name)),
-                                            }
-                                        },
-                                    }
-                                }
-                            }
+    /// Returns the value stored when a flag is set. Negatable flags store `ParameterValue::Bool(true)`.
+    fn flag_value(&self) -> ParameterValue {
+        if self.negatable {
+            ParameterValue::Bool(true)
+        } else {
+            ParameterValue::Flag
+        }
+    }
 
-                            if !parameter_exists {
-                                println!("Unknown parameter: {}", arg);
-                                self.abort_flag = true;
-                            }
-                        },
-                    }
-                },
-                None => break,
-            }
+    /// Returns true if the parameter requires a value when used.
+    fn takes_value(&self) -> bool {
+        !matches!(self.parameter_type, ParameterType::Flag | ParameterType::Counter)
+    }
+
+    /// Sets a flag parameter, or increments a counter parameter.
+    fn set_flag(&mut self) {
+        self.value = match (&self.parameter_type, &self.value) {
+            (ParameterType::Counter, ParameterValue::Counter(count)) => ParameterValue::Counter(count.saturating_add(1)),
+            (ParameterType::Counter, _) => ParameterValue::Counter(1),
+            _ => self.flag_value(),
+        };
+    }
+
+    /// Returns the `--no-` aliases of a negatable flag.
+    fn negated_aliases(&self) -> Vec<String> {
+        if !self.negatable {
+            return Vec::new();
         }
+
+        self.aliases.iter()
+            .filter_map(|alias| alias.strip_prefix("--"))
+            .map(|name| format!("--no-{}", name))
+            .collect()
     }
 
-    /// Sets the text to print when the `--help` parameter is used.
-    pub fn set_help_text(&mut self, help_text: &str) {
-        self.help_text = Some(help_text.to_owned());
+    /// Returns the values from `load_from_file`, or the values from the config parameter's file if
+    /// `from_config_parameter` is true.
+    fn config_layer(&mut self, from_config_parameter: bool) -> &mut ParameterValue {
+        if from_config_parameter {
+            &mut self.config_value
+        } else {
+            &mut self.file_value
+        }
     }
 
-    /// Prints the help text. Prints a default message if the help text is not set.
-    fn print_help_text(&self) {
-        match &self.help_text {
-            Some(help_text) => println!("{}", help_text),
-            None => println!("No help text has been set."),
+    /// Stores a parsed value. List values are appended to the existing list instead of replacing it.
+    fn store_value(&mut self, value: ParameterValue) {
+        merge_value(&mut self.value, value);
+    }
+}
+
+/// Constraints reported by `CommandLineProcessor::validate`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationError {
+    /// The required parameter has no value.
+    MissingRequired(String),
+
+    /// More than one parameter of an exclusive group was passed. Contains the names of the parameters that were passed.
+    Conflict(Vec<String>),
+
+    /// A parameter was passed without a parameter it requires.
+    MissingDependency {
+        /// Name of the parameter that was passed.
+        parameter: String,
+
+        /// Name of the required parameter that has no value.
+        requires: String,
+    },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValidationError::MissingRequired(parameter) => write!(f, "Missing required parameter {}", parameter),
+            ValidationError::Conflict(parameters) => write!(f, "Parameters can't be used together: {}", parameters.join(", ")),
+            ValidationError::MissingDependency { parameter, requires } => write!(f, "Parameter {} requires parameter {}", parameter, requires),
         }
     }
+}
 
-    /// Sets the text to print when the `--version` parameter is used.
-    pub fn set_version_text(&mut self, version_text: &str) {
-        self.version_text = Some(version_text.to_owned());
+impl Error for ValidationError {}
+
+/// Error returned by the `require_*` getters of `CommandLineProcessor` when a parameter has no value of the requested
+/// type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MissingParam {
+    /// Name of the parameter.
+    pub parameter: String,
+
+    /// Type of the value that was requested.
+    pub expected: ParameterType,
+}
+
+impl fmt::Display for MissingParam {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Parameter {} has no {:?} value", self.parameter, self.expected)
     }
+}
 
-    /// Prints the version text. Prints a default message if the version text is not set.
-    fn print_version_text(&self) {
-        match &self.version_text {
-            Some(version_text) => println!("{}", version_text),
-            None => println!("No version text has been set."),
+impl Error for MissingParam {}
+
+/// Errors that can occur while loading parameter values from a config file.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The config file could not be read.
+    Io(io::Error),
+
+    /// A line in the config file is not a `key = value` pair.
+    Syntax {
+        /// Line number, starting at 1.
+        line: usize,
+    },
+
+    /// The value on a line of the config file could not be parsed.
+    Value {
+        /// Line number, starting at 1.
+        line: usize,
+
+        /// Error returned when parsing the value.
+        source: ParseError,
+    },
+
+    /// The TOML config file is not valid TOML.
+    #[cfg(feature = "toml")]
+    Toml(toml::de::Error),
+
+    /// The type of a value in the TOML config file can't be converted to the parameter's type.
+    TypeMismatch {
+        /// Key of the value, which is the parameter name.
+        key: String,
+
+        /// Type of the parameter.
+        expected: ParameterType,
+    },
+
+    /// A value in the TOML config file could not be parsed.
+    InvalidValue {
+        /// Key of the value, which is the parameter name.
+        key: String,
+
+        /// Error returned when parsing the value.
+        source: ParseError,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Io(err) => write!(f, "Unable to read config file: {}", err),
+            ConfigError::Syntax { line } => write!(f, "Expected key = value on line {} of config file", line),
+            ConfigError::Value { line, source } => write!(f, "Invalid value on line {} of config file: {}", line, source),
+            #[cfg(feature = "toml")]
+            ConfigError::Toml(err) => write!(f, "Unable to parse TOML config file: {}", err),
+            ConfigError::TypeMismatch { key, expected } => write!(f, "Value of {} in config file can't be used as {:?}", key, expected),
+            ConfigError::InvalidValue { key, source } => write!(f, "Invalid value for {} in config file: {}", key, source),
         }
     }
+}
 
-    /// Returns the `ParameterValue` for the specified parameter. Returns `ParameterValue::None` if the parameter doesn't exist.
-    pub fn get_parameter_value(&self, parameter_name: &str) -> &ParameterValue {
-        match self.parameters.get(parameter_name) {
-            Some(parameter) => &parameter.value,
-            None => &ParameterValue::None,
+impl Error for ConfigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ConfigError::Io(err) => Some(err),
+            ConfigError::Syntax { .. } => None,
+            ConfigError::Value { source, .. } => Some(source),
+            #[cfg(feature = "toml")]
+            ConfigError::Toml(err) => Some(err),
+            ConfigError::TypeMismatch { .. } => None,
+            ConfigError::InvalidValue { source, .. } => Some(source),
         }
     }
+}
 
-    /// Returns true if the `CommandLineProcessor` reads `--help` or `--version` in the parameter list.
-    pub fn abort_flag(&self) -> bool {
-        self.abort_flag
+impl From<io::Error> for ConfigError {
+    fn from(err: io::Error) -> ConfigError {
+        ConfigError::Io(err)
+    }
+}
+
+/// Read-only view of a registered parameter.
+#[derive(Debug)]
+pub struct ParameterInfo<'a> {
+    /// Name of the parameter.
+    pub name: &'a str,
+
+    /// Type of the parameter.
+    pub parameter_type: &'a ParameterType,
+
+    /// Aliases that match the parameter on the command line.
+    pub aliases: &'a [String],
+
+    /// Description shown in the generated help text.
+    pub description: Option<&'a str>,
+}
+
+/// Result of `CommandLineProcessor::analyze`, describing what parsing a set of arguments would do.
+#[derive(Debug)]
+pub struct ParseReport {
+    /// Parameters and positional arguments given in the arguments, with the values they would have, in the order they were added.
+    /// The value of a secret parameter is `ParameterValue::String("***")`.
+    pub matched: Vec<(String, ParameterValue)>,
+
+    /// Arguments that did not match any parameter.
+    pub unknown: Vec<String>,
+
+    /// Errors that parsing would return, in the order of the arguments.
+    pub errors: Vec<ParseError>,
+
+    /// True if the arguments ask for the help or version text, or use a flag whose `set_on_set` function stops parsing.
+    pub info_requested: bool,
+}
+
+/// Definition of a parameter, built fluently and added with `CommandLineProcessor::add`.
+/// 
+/// ```
+/// use cmdpro::{CommandLineProcessor, ParameterSpec};
+/// 
+/// let mut processor = CommandLineProcessor::new();
+/// let count = processor.add(ParameterSpec::builder("count")
+///     .aliases(["--count", "-c"])
+///     .uinteger()
+///     .default(10)
+///     .description("Number of items to process"));
+/// 
+/// processor.try_parse_args(Vec::new()).unwrap();
+/// assert_eq!(processor.get_uinteger(&count), Some(10));
+/// ```
+pub struct ParameterSpec {
+    name: String,
+    parameter_type: ParameterType,
+    aliases: Vec<String>,
+    default: ParameterValue,
+    required: bool,
+    description: Option<String>,
+    value_name: Option<String>,
+    validator: Option<Box<Validator>>,
+    group: Option<String>,
+    hidden: bool,
+    secret: bool,
+}
+
+impl ParameterSpec {
+    /// Starts the definition of a `ParameterType::Flag` parameter with no aliases.
+    pub fn builder(name: &str) -> ParameterSpec {
+        ParameterSpec {
+            name: name.to_owned(),
+            parameter_type: ParameterType::Flag,
+            aliases: Vec::new(),
+            default: ParameterValue::None,
+            required: false,
+            description: None,
+            value_name: None,
+            validator: None,
+            group: None,
+            hidden: false,
+            secret: false,
+        }
     }
-}
\ No newline at end of file
+
+    /// Sets the aliases that match the parameter on the command line.
+    pub fn aliases<A: IntoIterator>(mut self, aliases: A) -> ParameterSpec where A::Item: AsRef<str> {
+        self.aliases = aliases.into_iter().map(|alias| alias.as_ref().to_owned()).collect();
+        self
+    }
+
+    /// Sets the type of the parameter.
+    pub fn parameter_type(mut self, parameter_type: ParameterType) -> ParameterSpec {
+        self.parameter_type = parameter_type;
+        self
+    }
+
+    /// Makes the parameter a `ParameterType::Flag` parameter.
+    pub fn flag(self) -> ParameterSpec {
+        self.parameter_type(ParameterType::Flag)
+    }
+
+    /// Makes the parameter a `ParameterType::UInteger` parameter.
+    pub fn uinteger(self) -> ParameterSpec {
+        self.parameter_type(ParameterType::UInteger)
+    }
+
+    /// Makes the parameter a `ParameterType::Integer` parameter.
+    pub fn integer(self) -> ParameterSpec {
+        self.parameter_type(ParameterType::Integer)
+    }
+
+    /// Makes the parameter a `ParameterType::Float` parameter.
+    pub fn float(self) -> ParameterSpec {
+        self.parameter_type(ParameterType::Float)
+    }
+
+    /// Makes the parameter a `ParameterType::String` parameter.
+    pub fn string(self) -> ParameterSpec {
+        self.parameter_type(ParameterType::String)
+    }
+
+    /// Makes the parameter a `ParameterType::Path` parameter.
+    pub fn path(self) -> ParameterSpec {
+        self.parameter_type(ParameterType::Path)
+    }
+
+    /// Makes the parameter a `ParameterType::Bool` parameter.
+    pub fn bool(self) -> ParameterSpec {
+        self.parameter_type(ParameterType::Bool)
+    }
+
+    /// Sets the value returned when the parameter is not set. An integer is converted to the parameter's type when the
+    /// parameter is added, so `.uinteger().default(10)` stores `ParameterValue::UInteger(10)`.
+    pub fn default<V: Into<ParameterValue>>(mut self, default: V) -> ParameterSpec {
+        self.default = default.into();
+        self
+    }
+
+    /// Marks the parameter as required. See `CommandLineProcessor::set_required`.
+    pub fn required(mut self) -> ParameterSpec {
+        self.required = true;
+        self
+    }
+
+    /// Sets the description shown in the generated help text.
+    pub fn description(mut self, description: &str) -> ParameterSpec {
+        self.description = Some(description.to_owned());
+        self
+    }
+
+    /// Sets the name shown for the value in the generated help text. See `CommandLineProcessor::set_value_name`.
+    pub fn value_name(mut self, value_name: &str) -> ParameterSpec {
+        self.value_name = Some(value_name.to_owned());
+        self
+    }
+
+    /// Sets a function that checks each parsed value. See `CommandLineProcessor::set_validator`.
+    pub fn validator(mut self, validator: Box<Validator>) -> ParameterSpec {
+        self.validator = Some(validator);
+        self
+    }
+
+    /// Lists the parameter under a heading in the generated help text. See `CommandLineProcessor::set_group`.
+    pub fn group(mut self, group: &str) -> ParameterSpec {
+        self.group = Some(group.to_owned());
+        self
+    }
+
+    /// Hides the parameter from the generated help text. See `CommandLineProcessor::set_hidden`.
+    pub fn hidden(mut self) -> ParameterSpec {
+        self.hidden = true;
+        self
+    }
+
+    /// Keeps the parameter's value out of traces and serialized output. See `CommandLineProcessor::set_secret`.
+    pub fn secret(mut self) -> ParameterSpec {
+        self.secret = true;
+        self
+    }
+}
+
+/// Name of a parameter, returned when the parameter is added. Passing it to the getters instead of a string literal
+/// rules out looking up a misspelled name.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ParamKey(String);
+
+impl ParamKey {
+    /// Returns the name of the parameter.
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for ParamKey {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Command Line Processor
+#[derive(Clone)]
+pub struct CommandLineProcessor {
+    parameters: HashMap<String, Parameter>,
+    parameter_order: Vec<String>,
+    positionals: Vec<Parameter>,
+    exclusive_groups: Vec<Vec<String>>,
+    help_groups: Vec<String>,
+    help_sections: Vec<(String, String)>,
+    requirements: Vec<(String, Vec<String>)>,
+    subcommands: Vec<(String, CommandLineProcessor)>,
+    selected_subcommand: Option<String>,
+    help_text: Option<String>,
+    version_text: Option<String>,
+    program_name: Option<String>,
+    about: Option<String>,
+    help_width: Option<usize>,
+    help_aliases: Vec<String>,
+    version_aliases: Vec<String>,
+    builtin_flags_enabled: bool,
+    case_insensitive: bool,
+    allow_abbreviations: bool,
+    response_files: bool,
+    auto_short_aliases: bool,
+    trailing_args: Vec<String>,
+    unknown_parameters: Vec<String>,
+    trace: Vec<(String, String)>,
+    warnings: Vec<String>,
+    setup_warnings: usize,
+    print_unknown_parameters: bool,
+    print_warnings: bool,
+    prompt_on_missing: bool,
+    config_parameter: Option<String>,
+    print_help_if_no_args: bool,
+    no_args: bool,
+    mode: ParseMode,
+    info_printed: bool,
+    parse_failed: bool,
+    abort_flag: bool,
+    quiet: bool,
+}
+
+impl CommandLineProcessor {
+    /// Returns a new `CommandLineProcessor`.
+    pub fn new() -> CommandLineProcessor {
+        CommandLineProcessor {
+            parameters: HashMap::new(),
+            parameter_order: Vec::new(),
+            positionals: Vec::new(),
+            exclusive_groups: Vec::new(),
+            help_groups: Vec::new(),
+            help_sections: Vec::new(),
+            requirements: Vec::new(),
+            subcommands: Vec::new(),
+            selected_subcommand: None,
+            help_text: None,
+            version_text: None,
+            program_name: None,
+            about: None,
+            help_width: None,
+            help_aliases: vec!["--help".to_owned(), "-h".to_owned(), "--h".to_owned()],
+            version_aliases: vec!["--version".to_owned(), "-V".to_owned(), "--v".to_owned()],
+            builtin_flags_enabled: true,
+            case_insensitive: false,
+            allow_abbreviations: false,
+            response_files: false,
+            auto_short_aliases: false,
+            trailing_args: Vec::new(),
+            unknown_parameters: Vec::new(),
+            trace: Vec::new(),
+            warnings: Vec::new(),
+            setup_warnings: 0,
+            print_unknown_parameters: false,
+            print_warnings: false,
+            prompt_on_missing: false,
+            config_parameter: None,
+            print_help_if_no_args: false,
+            no_args: false,
+            mode: ParseMode::Strict,
+            info_printed: false,
+            parse_failed: false,
+            abort_flag: false,
+            quiet: false,
+        }
+    }
+
+    /// Returns a `CommandLineProcessorBuilder` for configuring a `CommandLineProcessor` fluently.
+    pub fn builder() -> CommandLineProcessorBuilder {
+        CommandLineProcessorBuilder::new()
+    }
+
+    /// Add a parameter to be parsed. Returns a `ParamKey` that can be passed to the getters.
+    /// 
+    /// An alias that doesn't start with `-` is accepted but adds a warning to `warnings`.
+    /// 
+    /// # Panics
+    /// Panics if the parameter name or one of its aliases has already been added, or if the name or an alias is empty.
+    pub fn add_parameter<A: IntoIterator>(&mut self, parameter_name: &str, parameter_type: ParameterType, aliases: A) -> ParamKey where A::Item: AsRef<str> {
+        self.add_parameter_with_default(parameter_name, parameter_type, aliases, ParameterValue::None)
+    }
+
+    /// Add a parameter to be parsed. The default value is returned when the parameter is not set.
+    /// Returns a `ParamKey` that can be passed to the getters.
+    /// 
+    /// # Panics
+    /// Panics if the default value does not match the parameter type.
+    /// It will also panic if the parameter name or one of its aliases has already been added, or if the name or an alias is empty.
+    pub fn add_parameter_with_default<A: IntoIterator>(&mut self, parameter_name: &str, parameter_type: ParameterType, aliases: A, default: ParameterValue) -> ParamKey where A::Item: AsRef<str> {
+        if !value_matches_type(&default, &parameter_type) {
+            panic!("Default value for parameter {} does not match its type", parameter_name);
+        }
+
+        if self.has_parameter(parameter_name) {
+            panic!("Parameter {} has already been added", parameter_name);
+        }
+
+        if parameter_name.is_empty() {
+            panic!("Parameter name can't be empty");
+        }
+
+        let aliases: Vec<String> = aliases.into_iter().map(|alias| alias.as_ref().to_owned()).collect();
+        for alias in &aliases {
+            if alias.is_empty() {
+                panic!("Parameter {} has an empty alias", parameter_name);
+            }
+
+            // An alias without a dash still matches, but is easily confused with a positional argument or subcommand.
+            if !alias.starts_with('-') {
+                self.add_setup_warning(format!("Alias {} of parameter {} does not start with -", alias, parameter_name));
+            }
+
+            if let Some(existing) = self.parameters.values().find(|parameter| parameter.aliases.contains(alias)) {
+                panic!("Alias {} of parameter {} is already used by parameter {}", alias, parameter_name, existing.parameter_name);
+            }
+        }
+
+        let mut parameter = Parameter::new(parameter_name, parameter_type, aliases);
+        parameter.default = default;
+
+        self.parameters.insert(parameter_name.to_owned(), parameter);
+        self.parameter_order.push(parameter_name.to_owned());
+
+        if self.auto_short_aliases {
+            self.add_short_aliases(parameter_name);
+        }
+
+        ParamKey(parameter_name.to_owned())
+    }
+
+    /// Adds a short alias such as `-v` for each long alias such as `--verbose` of the parameter.
+    /// A short alias that is already used is not added, and a warning is added to `warnings` instead.
+    fn add_short_aliases(&mut self, parameter_name: &str) {
+        let long_aliases: Vec<String> = match self.parameters.get(parameter_name) {
+            Some(parameter) => parameter.aliases.iter().filter(|alias| alias.starts_with("--")).cloned().collect(),
+            None => return,
+        };
+
+        for long_alias in long_aliases {
+            let short_alias = match long_alias.chars().nth(2) {
+                Some(c) if c.is_alphanumeric() => format!("-{}", c),
+                _ => continue,
+            };
+
+            let owner = self.parameters.values()
+                .find(|parameter| parameter.aliases.contains(&short_alias))
+                .map(|parameter| parameter.parameter_name.clone());
+            let builtin = self.builtin_flags_enabled && (self.help_aliases.contains(&short_alias) || self.version_aliases.contains(&short_alias));
+
+            match owner {
+                Some(ref owner) if owner == parameter_name => (),
+                Some(owner) => self.add_setup_warning(format!("Short alias {} for parameter {} is already used by parameter {}", short_alias, parameter_name, owner)),
+                None if builtin => self.add_setup_warning(format!("Short alias {} for parameter {} is already used by the help or version text", short_alias, parameter_name)),
+                None => {
+                    if let Some(parameter) = self.parameters.get_mut(parameter_name) {
+                        parameter.aliases.push(short_alias);
+                    }
+                },
+            }
+        }
+    }
+
+    /// Add a parameter defined with `ParameterSpec::builder`.
+    /// 
+    /// # Panics
+    /// Panics in the same cases as `add_parameter_with_default`.
+    pub fn add(&mut self, spec: ParameterSpec) -> ParamKey {
+        let name = spec.name;
+        let default = integer_default(spec.default, &spec.parameter_type);
+        let key = self.add_parameter_with_default(&name, spec.parameter_type, spec.aliases, default);
+
+        if spec.required {
+            self.set_required(&name);
+        }
+        if let Some(description) = spec.description {
+            self.set_description(&name, &description);
+        }
+        if let Some(value_name) = spec.value_name {
+            self.set_value_name(&name, &value_name);
+        }
+        if let Some(validator) = spec.validator {
+            self.set_validator(&name, validator);
+        }
+        if let Some(group) = spec.group {
+            self.set_group(&name, &group);
+        }
+        if spec.hidden {
+            self.set_hidden(&name);
+        }
+        if spec.secret {
+            self.set_secret(&name);
+        }
+
+        key
+    }
+
+    /// Copies the parameter definitions of another `CommandLineProcessor`, such as a shared set of global flags.
+    /// 
+    /// Each parameter keeps its aliases, default value and settings, but not its parsed value or a value loaded from
+    /// a config file. Positional arguments, subcommands, exclusive groups, requirements and processor-wide settings
+    /// are not copied.
+    /// 
+    /// A parameter is skipped if its name or one of its aliases is already used by a parameter of this
+    /// `CommandLineProcessor`, so existing parameters are never replaced. A greedy parameter is also skipped if this
+    /// `CommandLineProcessor` already has a greedy parameter. The other parameters are still copied, and the names of
+    /// the skipped parameters are returned as the error.
+    pub fn merge(&mut self, other: &CommandLineProcessor) -> Result<(), Vec<String>> {
+        let mut skipped = Vec::new();
+
+        for parameter in other.ordered_parameters() {
+            let collides = self.has_parameter(&parameter.parameter_name) || self.parameters.values()
+                .any(|existing| existing.aliases.iter().any(|alias| parameter.aliases.contains(alias))
+                    || (existing.greedy && parameter.greedy));
+            if collides {
+                skipped.push(parameter.parameter_name.clone());
+                continue;
+            }
+
+            let mut parameter = parameter.clone();
+            parameter.value = ParameterValue::None;
+            parameter.file_value = ParameterValue::None;
+            parameter.config_value = ParameterValue::None;
+            parameter.was_set = false;
+            parameter.prompted = false;
+
+            if let Some(group) = &parameter.group {
+                if !self.help_groups.contains(group) {
+                    self.help_groups.push(group.clone());
+                }
+            }
+
+            self.parameter_order.push(parameter.parameter_name.clone());
+            self.parameters.insert(parameter.parameter_name.clone(), parameter);
+        }
+
+        if skipped.is_empty() {
+            Ok(())
+        } else {
+            Err(skipped)
+        }
+    }
+
+    /// Add a parameter to be parsed with a one-line description for the help text.
+    pub fn add_parameter_with_description<A: IntoIterator>(&mut self, parameter_name: &str, parameter_type: ParameterType, aliases: A, description: &str) -> ParamKey where A::Item: AsRef<str> {
+        let key = self.add_parameter(parameter_name, parameter_type, aliases);
+        self.set_description(parameter_name, description);
+        key
+    }
+
+    /// Allows a `ParameterType::UInteger` parameter to take a unit suffix, so `10k` is 10000 and `2mi` is 2097152.
+    /// The suffixes `k`, `m` and `g` multiply by powers of 1000 and `ki`, `mi` and `gi` by powers of 1024, ignoring case.
+    /// Disabled by default.
+    pub fn set_allow_size_suffix(&mut self, parameter_name: &str, allow: bool) {
+        if let Some(parameter) = self.parameters.get_mut(parameter_name) {
+            parameter.allow_size_suffix = allow;
+        }
+    }
+
+    /// Sets the inclusive range of values accepted by a `ParameterType::UInteger` parameter.
+    pub fn set_range(&mut self, parameter_name: &str, min: u32, max: u32) {
+        if let Some(parameter) = self.parameters.get_mut(parameter_name) {
+            parameter.range = Some((min, max));
+        }
+    }
+
+    /// Requires the paths given to a path parameter to be absolute. The check only looks at the path itself and
+    /// does not touch the filesystem. Replaces a previous `set_require_relative`.
+    pub fn set_require_absolute(&mut self, parameter_name: &str) {
+        if let Some(parameter) = self.parameters.get_mut(parameter_name) {
+            parameter.require_absolute = true;
+            parameter.require_relative = false;
+        }
+    }
+
+    /// Requires the paths given to a path parameter to be relative. The check only looks at the path itself and
+    /// does not touch the filesystem. Replaces a previous `set_require_absolute`.
+    pub fn set_require_relative(&mut self, parameter_name: &str) {
+        if let Some(parameter) = self.parameters.get_mut(parameter_name) {
+            parameter.require_relative = true;
+            parameter.require_absolute = false;
+        }
+    }
+
+    /// Expands a leading `~` to the home directory and `$VAR` or `${VAR}` to the value of the environment variable in
+    /// the values of a path parameter. The policy decides what happens when a variable, or `HOME` for `~`, is not set.
+    pub fn set_expand_path(&mut self, parameter_name: &str, policy: UnsetVariablePolicy) {
+        if let Some(parameter) = self.parameters.get_mut(parameter_name) {
+            parameter.expand_path = Some(policy);
+        }
+    }
+
+    /// Hides a parameter from the generated help text, completion scripts and suggestions for unknown parameters.
+    /// Hidden parameters are still parsed.
+    pub fn set_hidden(&mut self, parameter_name: &str) {
+        if let Some(parameter) = self.parameters.get_mut(parameter_name) {
+            parameter.hidden = true;
+        }
+    }
+
+    /// Marks a parameter as secret, such as a password. The value is replaced with `***` in `parse_trace`, `analyze`,
+    /// `values_snapshot`, `to_json` and trace logs, and is not echoed when it is asked for by `set_prompt_on_missing`.
+    /// Echo is turned off with `stty`. If that fails, or on platforms other than unix, the parameter is not asked for
+    /// and a warning is added to `warnings` instead.
+    pub fn set_secret(&mut self, parameter_name: &str) {
+        if let Some(parameter) = self.parameters.get_mut(parameter_name) {
+            parameter.secret = true;
+        }
+    }
+
+    /// Marks a parameter as deprecated. The parameter still works, but each use adds a warning with the message,
+    /// such as `use --new-flag instead`, to `warnings`.
+    pub fn set_deprecated(&mut self, parameter_name: &str, message: &str) {
+        if let Some(parameter) = self.parameters.get_mut(parameter_name) {
+            parameter.deprecated = Some(message.to_owned());
+        }
+    }
+
+    /// Makes a `ParameterType::Flag` parameter negatable, so each long alias such as `--color` also has a `--no-color` form.
+    /// 
+    /// Negatable flags store `ParameterValue::Bool(true)` when set and `ParameterValue::Bool(false)` when negated,
+    /// so an unset flag can be told apart from one that was explicitly turned off.
+    pub fn set_negatable(&mut self, parameter_name: &str) {
+        if let Some(parameter) = self.parameters.get_mut(parameter_name) {
+            parameter.negatable = true;
+        }
+    }
+
+    /// Splits each value of a `ParameterType::StringList` or `ParameterType::PathList` parameter on the delimiter,
+    /// so `--tags a,b,c` adds three values when the delimiter is `','`.
+    /// 
+    /// Empty values, such as the one in `a,,b`, are dropped unless `set_keep_empty_values` is enabled.
+    pub fn set_delimiter(&mut self, parameter_name: &str, delimiter: char) {
+        if let Some(parameter) = self.parameters.get_mut(parameter_name) {
+            parameter.delimiter = Some(delimiter);
+        }
+    }
+
+    /// Sets whether empty values produced by splitting on the delimiter are kept. Disabled by default.
+    pub fn set_keep_empty_values(&mut self, parameter_name: &str, keep: bool) {
+        if let Some(parameter) = self.parameters.get_mut(parameter_name) {
+            parameter.keep_empty_values = keep;
+        }
+    }
+
+    /// Lists the parameter under a heading of its own in the generated help text.
+    /// Groups are shown after the ungrouped parameters in the order they were first used.
+    pub fn set_group(&mut self, parameter_name: &str, group: &str) {
+        if let Some(parameter) = self.parameters.get_mut(parameter_name) {
+            parameter.group = Some(group.to_owned());
+            if !self.help_groups.iter().any(|help_group| help_group == group) {
+                self.help_groups.push(group.to_owned());
+            }
+        }
+    }
+
+    /// Allows a String or file parameter to read from standard input when its value is `-`. Disabled by default.
+    /// 
+    /// A String parameter given `-` blocks until standard input is closed and uses everything read, without trailing
+    /// newlines, as its value. A Path or ExistingPath parameter keeps `-` as its path without checking that it exists,
+    /// so the program can read standard input in place of the file.
+    pub fn set_allow_stdin(&mut self, parameter_name: &str, allow: bool) {
+        if let Some(parameter) = self.parameters.get_mut(parameter_name) {
+            parameter.allow_stdin = allow;
+        }
+    }
+
+    /// Sets a function that checks each parsed value of the parameter. An error message returned by the validator
+    /// is reported as `ParseErrorKind::ValidationFailed`.
+    pub fn set_validator(&mut self, parameter_name: &str, validator: Box<Validator>) {
+        if let Some(parameter) = self.parameters.get_mut(parameter_name) {
+            parameter.validator = Some(Arc::from(validator));
+        }
+    }
+
+    /// Sets a function that runs as soon as the flag is parsed, before the rest of the arguments.
+    /// 
+    /// If `abort` is true, parsing stops after the function runs and the abort flag is set, as it is when the help
+    /// text is printed. The function is not run by `analyze`.
+    /// 
+    /// # Panics
+    /// Panics if the parameter is not a flag or counter parameter.
+    pub fn set_on_set(&mut self, parameter_name: &str, callback: Box<Callback>, abort: bool) {
+        if let Some(parameter) = self.parameters.get_mut(parameter_name) {
+            if parameter.takes_value() {
+                panic!("Parameter {} takes a value so it can't run a function when it is set", parameter_name);
+            }
+            parameter.on_set = Some((Arc::from(callback), abort));
+        }
+    }
+
+    /// Sets an environment variable to read the parameter's value from when it is not passed on the command line.
+    /// 
+    /// The value is parsed in the same way as a value in a config file. A `ParameterType::Flag` parameter is set by
+    /// `true`, `yes` or `1` and left unset by `false`, `no` or `0`, which turn a negatable flag off, and a
+    /// `ParameterType::Counter` parameter reads its count from the variable.
+    pub fn set_env_fallback(&mut self, parameter_name: &str, env_var: &str) {
+        if let Some(parameter) = self.parameters.get_mut(parameter_name) {
+            parameter.env_var = Some(env_var.to_owned());
+        }
+    }
+
+    /// Add a group of parameters that can't be used together. `validate` reports an error if more than one is passed.
+    /// Only parameters given on the command line are counted, not defaults or values from a config file or environment variable.
+    pub fn add_exclusive_group(&mut self, parameter_names: Vec<String>) {
+        self.exclusive_groups.push(parameter_names);
+    }
+
+    /// Records that passing a parameter requires the listed parameters to also have values. Checked by `validate`.
+    /// The requirement applies only when the parameter is given on the command line, but a required parameter may
+    /// get its value from anywhere.
+    pub fn add_requirement(&mut self, parameter_name: &str, requires: Vec<String>) {
+        self.requirements.push((parameter_name.to_owned(), requires));
+    }
+
+    /// Returns true if the parameter or positional argument was given on the command line.
+    /// 
+    /// Values from defaults, config files and environment variables are not counted.
+    pub fn was_provided<K: AsRef<str> + ?Sized>(&self, parameter_name: &K) -> bool {
+        let parameter_name = parameter_name.as_ref();
+        match self.parameters.get(parameter_name) {
+            Some(parameter) => parameter.was_set,
+            None => self.positionals.iter().any(|positional| positional.parameter_name == parameter_name && positional.was_set),
+        }
+    }
+
+    /// Returns true if a parameter with the specified name has been added.
+    pub fn has_parameter(&self, parameter_name: &str) -> bool {
+        self.parameters.contains_key(parameter_name)
+    }
+
+    /// Checks that no alias is claimed by more than one parameter, including the built-in help and version aliases.
+    /// Returns a message listing each conflicting alias and the parameters that claim it.
+    pub fn check_aliases(&self) -> Result<(), String> {
+        let parameters = self.ordered_parameters();
+
+        let mut claims: Vec<(&str, &str)> = Vec::new();
+        claims.extend(self.help_aliases.iter().filter(|alias| self.is_builtin_alias(&self.help_aliases, alias)).map(|alias| ("help", alias.as_str())));
+        claims.extend(self.version_aliases.iter().filter(|alias| self.is_builtin_alias(&self.version_aliases, alias)).map(|alias| ("version", alias.as_str())));
+
+        for parameter in parameters {
+            for alias in &parameter.aliases {
+                claims.push((&parameter.parameter_name, alias));
+            }
+        }
+
+        let mut conflicts = Vec::new();
+        for (index, &(owner, alias)) in claims.iter().enumerate() {
+            for &(other_owner, other_alias) in claims.iter().skip(index + 1) {
+                if alias == other_alias && owner != other_owner {
+                    conflicts.push(format!("Alias {} is used by both {} and {}", alias, owner, other_owner));
+                }
+            }
+        }
+
+        if conflicts.is_empty() {
+            Ok(())
+        } else {
+            Err(conflicts.join("\n"))
+        }
+    }
+
+    /// Add a parameter that only accepts one of the given choices. The chosen value is stored as `ParameterValue::String`.
+    /// 
+    /// # Panics
+    /// Panics if the parameter name or one of its aliases has already been added.
+    pub fn add_choice_parameter<A: IntoIterator>(&mut self, parameter_name: &str, aliases: A, choices: Vec<String>) -> ParamKey where A::Item: AsRef<str> {
+        let key = self.add_parameter(parameter_name, ParameterType::String, aliases);
+        if let Some(parameter) = self.parameters.get_mut(parameter_name) {
+            parameter.choices = Some(choices);
+        }
+        key
+    }
+
+    /// Add a parameter whose value can have one of several types, such as a `--timeout` that takes a number of
+    /// seconds or the keyword `infinite`. Returns a `ParamKey` that can be passed to the getters.
+    /// 
+    /// The value is converted to each type in the order given and stored as the first one that succeeds, so
+    /// `ParameterType::String` should come last. If no type matches, a value equal to one of the keywords is stored
+    /// as `ParameterValue::String`, and any other value is a `ParseErrorKind::NoMatchingType`.
+    /// 
+    /// # Panics
+    /// Panics if no types are given, or if one of the types is a flag, counter or list type.
+    /// It will also panic if the parameter name or one of its aliases has already been added.
+    pub fn add_union_parameter<A: IntoIterator>(&mut self, parameter_name: &str, aliases: A, types: Vec<ParameterType>, keywords: Vec<String>) -> ParamKey where A::Item: AsRef<str> {
+        let first_type = match types.first() {
+            Some(first_type) => first_type.clone(),
+            None => panic!("Parameter {} must have at least one type", parameter_name),
+        };
+
+        for parameter_type in &types {
+            match parameter_type {
+                ParameterType::Flag | ParameterType::Counter | ParameterType::PathList | ParameterType::StringList => {
+                    panic!("Parameter {} can't have the type {:?} as one of several types", parameter_name, parameter_type)
+                },
+                _ => (),
+            }
+        }
+
+        let key = self.add_parameter(parameter_name, first_type, aliases);
+        if let Some(parameter) = self.parameters.get_mut(parameter_name) {
+            parameter.union_types = types;
+            parameter.keywords = keywords;
+        }
+        key
+    }
+
+    /// Add a list parameter that takes a fixed number of values from the arguments that follow it, as in `--coords 1 2 3`.
+    /// 
+    /// # Panics
+    /// Panics if the parameter type is not `ParameterType::StringList` or `ParameterType::PathList`.
+    /// It will also panic if the parameter name or one of its aliases has already been added.
+    pub fn add_nargs_parameter<A: IntoIterator>(&mut self, parameter_name: &str, aliases: A, parameter_type: ParameterType, count: usize) -> ParamKey where A::Item: AsRef<str> {
+        match parameter_type {
+            ParameterType::StringList | ParameterType::PathList => (),
+            _ => panic!("Parameter {} takes {} values so it must have a list type", parameter_name, count),
+        }
+
+        let key = self.add_parameter(parameter_name, parameter_type, aliases);
+        if let Some(parameter) = self.parameters.get_mut(parameter_name) {
+            parameter.nargs = Some(count);
+        }
+        key
+    }
+
+    /// Makes a parameter take every remaining argument as its value, including arguments that look like parameters,
+    /// as in `--exec ls -l`. A `ParameterType::String` parameter stores the arguments joined with spaces and a
+    /// `ParameterType::StringList` parameter stores them as a list.
+    /// 
+    /// # Panics
+    /// Panics if the parameter is not a String or StringList parameter, or if another parameter already takes the remaining arguments.
+    pub fn set_greedy(&mut self, parameter_name: &str) {
+        if let Some(existing) = self.parameters.values().find(|parameter| parameter.greedy && parameter.parameter_name != parameter_name) {
+            panic!("Parameter {} can't take the remaining arguments because parameter {} already does", parameter_name, existing.parameter_name);
+        }
+
+        if let Some(parameter) = self.parameters.get_mut(parameter_name) {
+            match parameter.parameter_type {
+                ParameterType::String | ParameterType::StringList => parameter.greedy = true,
+                _ => panic!("Parameter {} takes the remaining arguments so it must be a String or StringList parameter", parameter_name),
+            }
+        }
+    }
+
+    /// Sets whether the choices of a choice parameter are matched case-insensitively. Matching is case-sensitive by default.
+    /// 
+    /// The stored value is always the choice as it was added.
+    pub fn set_choices_case_insensitive(&mut self, parameter_name: &str, case_insensitive: bool) {
+        if let Some(parameter) = self.parameters.get_mut(parameter_name) {
+            parameter.choices_case_insensitive = case_insensitive;
+        }
+    }
+
+    /// Returns the names of the registered parameters in the order they were added.
+    pub fn parameter_names(&self) -> impl Iterator<Item = &str> {
+        self.iter_parameters().map(|parameter| parameter.name)
+    }
+
+    /// Returns a read-only view of each registered parameter in the order they were added.
+    pub fn iter_parameters(&self) -> impl Iterator<Item = ParameterInfo<'_>> {
+        self.ordered_parameters().into_iter().map(|parameter| ParameterInfo {
+            name: &parameter.parameter_name,
+            parameter_type: &parameter.parameter_type,
+            aliases: &parameter.aliases,
+            description: parameter.description.as_deref(),
+        })
+    }
+
+    /// Returns the registered parameters in the order they were added.
+    fn ordered_parameters(&self) -> Vec<&Parameter> {
+        self.parameter_order.iter()
+            .filter_map(|name| self.parameters.get(name))
+            .collect()
+    }
+
+    /// Returns the registered parameters that are not hidden, in the order they were added.
+    fn visible_parameters(&self) -> Vec<&Parameter> {
+        self.ordered_parameters().into_iter()
+            .filter(|parameter| !parameter.hidden)
+            .collect()
+    }
+
+    /// Add a positional argument. Positional arguments are assigned in the order they are added.
+    /// 
+    /// A positional argument with a list type collects every remaining positional value.
+    /// 
+    /// # Panics
+    /// Panics if a positional argument with the same name has already been added.
+    pub fn add_positional(&mut self, parameter_name: &str, parameter_type: ParameterType) {
+        if self.positionals.iter().any(|positional| positional.parameter_name == parameter_name) {
+            panic!("Positional argument {} has already been added", parameter_name);
+        }
+
+        self.positionals.push(Parameter::new(parameter_name, parameter_type, Vec::new()));
+    }
+
+    /// Add a subcommand and return its `CommandLineProcessor` so its parameters can be added.
+    /// 
+    /// The first argument matching the subcommand's name selects it, and every argument after it is parsed by the subcommand.
+    /// Parameters of this processor are still recognized before the subcommand name.
+    /// 
+    /// # Panics
+    /// Panics if a subcommand with the same name has already been added.
+    pub fn add_subcommand(&mut self, subcommand_name: &str) -> &mut CommandLineProcessor {
+        if self.subcommands.iter().any(|(name, _)| name == subcommand_name) {
+            panic!("Subcommand {} has already been added", subcommand_name);
+        }
+
+        self.subcommands.push((subcommand_name.to_owned(), CommandLineProcessor::new()));
+        let index = self.subcommands.len() - 1;
+        &mut self.subcommands[index].1
+    }
+
+    /// Returns the `CommandLineProcessor` of a subcommand. Returns `None` if the subcommand doesn't exist.
+    pub fn get_subcommand(&self, subcommand_name: &str) -> Option<&CommandLineProcessor> {
+        self.subcommands.iter()
+            .find(|(name, _)| name == subcommand_name)
+            .map(|(_, subcommand)| subcommand)
+    }
+
+    /// Returns the name of the subcommand selected by the last parse. Returns `None` if no subcommand was used.
+    pub fn selected_subcommand(&self) -> Option<&str> {
+        self.selected_subcommand.as_deref()
+    }
+
+    /// Sets the description shown for a parameter in the generated help text.
+    pub fn set_description(&mut self, parameter_name: &str, description: &str) {
+        if let Some(parameter) = self.parameters.get_mut(parameter_name) {
+            parameter.description = Some(description.to_owned());
+        }
+    }
+
+    /// Sets the name shown for a parameter's value in the generated help text and usage line, so `PORT` shows
+    /// `--port <PORT>` instead of `--port <uint>`.
+    pub fn set_value_name(&mut self, parameter_name: &str, value_name: &str) {
+        if let Some(parameter) = self.parameters.get_mut(parameter_name) {
+            parameter.value_name = Some(value_name.to_owned());
+        }
+    }
+
+    /// Returns the description of a parameter. Returns `None` if the parameter doesn't exist or has no description.
+    pub fn get_description(&self, parameter_name: &str) -> Option<&str> {
+        match self.parameters.get(parameter_name) {
+            Some(parameter) => parameter.description.as_deref(),
+            None => None,
+        }
+    }
+
+    /// Returns the declared type of a parameter. Returns `None` if the parameter doesn't exist.
+    pub fn get_parameter_type(&self, parameter_name: &str) -> Option<&ParameterType> {
+        self.parameters.get(parameter_name).map(|parameter| &parameter.parameter_type)
+    }
+
+    /// Marks a parameter as required. Required parameters are checked by `validate`.
+    pub fn set_required(&mut self, parameter_name: &str) {
+        if let Some(parameter) = self.parameters.get_mut(parameter_name) {
+            parameter.required = true;
+        }
+    }
+
+    /// Parses the program's command line parameters.
+    /// 
+    /// Unknown parameters are collected and, in `ParseMode::Strict`, set the abort flag. They are printed if `set_print_unknown_parameters` is enabled.
+    /// Ambiguous abbreviations, invalid combined flags, values given to flags and extra positional arguments are printed
+    /// and set the abort flag.
+    /// Each printed error is followed by the usage line from `generate_usage_line`.
+    /// 
+    /// # Panics
+    /// Panics if the parameter type requires a value and no value is provided.
+    /// It will also panic if the parameter is the wrong type.
+    pub fn parse_command_line(&mut self) {
+        self.parse_args(env::args().skip(1));
+    }
+
+    /// Parses the program's command line parameters and exits the process if parsing failed or the abort flag is set.
+    /// 
+    /// This terminates the process with `std::process::exit` after the help or version text is printed (exit code 0)
+    /// or after a parsing error (exit code 2), and returns normally otherwise. Each error is printed to stderr,
+    /// followed by the usage line from `generate_usage_line`.
+    /// Use `try_parse_command_line` to handle these cases yourself.
+    pub fn parse_command_line_or_exit(&mut self) {
+        if let Err(errors) = self.try_parse_command_line() {
+            for err in &errors {
+                eprintln!("{}", err);
+                if let ParseErrorKind::UnknownParameter { argument } = err.kind() {
+                    if let Some(alias) = self.closest_alias(argument) {
+                        eprintln!("Did you mean {}?", alias);
+                    }
+                }
+            }
+            eprintln!("{}", self.generate_usage_line());
+            std::process::exit(2);
+        }
+
+        if self.abort_flag {
+            std::process::exit(self.suggested_exit_code().unwrap_or(2));
+        }
+    }
+
+    /// Parses the program's command line parameters, returning the errors instead of panicking.
+    /// 
+    /// Parsing continues after an error, so every error in the arguments is returned, in the order of the arguments.
+    /// An ambiguous abbreviation, invalid combined flag or extra positional argument also sets the abort flag, as does an unknown parameter in `ParseMode::Strict`.
+    /// In `ParseMode::Permissive` unknown parameters are collected without returning an error.
+    pub fn try_parse_command_line(&mut self) -> Result<(), Vec<ParseError>> {
+        self.try_parse_args(env::args().skip(1))
+    }
+
+    /// Parses the given arguments in the same way as `parse_command_line`.
+    /// 
+    /// The arguments should not include the executable name.
+    /// 
+    /// # Panics
+    /// Panics if the parameter type requires a value and no value is provided.
+    /// It will also panic if the parameter is the wrong type.
+    pub fn parse_args<I: IntoIterator<Item = String>>(&mut self, args: I) {
+        self.parse_args_os(args.into_iter().map(OsString::from));
+    }
+
+    /// Parses the given arguments in the same way as `try_parse_command_line`.
+    /// 
+    /// The arguments should not include the executable name.
+    /// Arguments after a standalone `--` are not parsed and are available from `get_trailing_args`.
+    /// 
+    /// This never panics on bad input: missing values, unknown parameters, out of range numbers and any other
+    /// invalid arguments are all returned as a `ParseError`. Only a function set with `set_validator` or `set_on_set`
+    /// can panic.
+    pub fn try_parse_args<I: IntoIterator<Item = String>>(&mut self, args: I) -> Result<(), Vec<ParseError>> {
+        self.try_parse_args_os(args.into_iter().map(OsString::from))
+    }
+
+    /// Splits a whole command line into arguments and parses them in the same way as `parse_args`.
+    /// 
+    /// Arguments are separated by whitespace. Single quotes keep everything up to the closing quote, double quotes
+    /// do the same but allow `\"` and `\\`, and outside of quotes a backslash keeps the next character, such as a space.
+    /// A trailing backslash is kept as it is. The line should not include the executable name.
+    /// 
+    /// # Panics
+    /// Panics in the same cases as `parse_command_line`.
+    pub fn parse_str(&mut self, line: &str) {
+        self.parse_args(split_command_line(line));
+    }
+
+    /// Splits a whole command line into arguments in the same way as `parse_str` and parses them in the same way as
+    /// `try_parse_args`.
+    pub fn try_parse_str(&mut self, line: &str) -> Result<(), Vec<ParseError>> {
+        self.try_parse_args(split_command_line(line))
+    }
+
+    /// Parses the program's command line parameters from `env::args_os`, so arguments that are not valid UTF-8
+    /// don't cause a panic.
+    /// 
+    /// Path values keep their original bytes. Other values and parameter names are converted to UTF-8, replacing invalid
+    /// sequences with `U+FFFD`. A path given as part of a `--key=value` argument is converted in the same way.
+    /// 
+    /// # Panics
+    /// Panics in the same cases as `parse_command_line`.
+    pub fn parse_command_line_os(&mut self) {
+        self.parse_args_os(env::args_os().skip(1));
+    }
+
+    /// Parses the program's command line parameters from `env::args_os`, returning the errors instead of panicking.
+    /// See `parse_command_line_os`.
+    pub fn try_parse_command_line_os(&mut self) -> Result<(), Vec<ParseError>> {
+        self.try_parse_args_os(env::args_os().skip(1))
+    }
+
+    /// Parses the given arguments in the same way as `parse_command_line_os`.
+    /// 
+    /// # Panics
+    /// Panics in the same cases as `parse_command_line`.
+    pub fn parse_args_os<I: IntoIterator<Item = OsString>>(&mut self, args: I) {
+        let errors = match self.try_parse_args_os(args) {
+            Ok(()) => return,
+            Err(errors) => errors,
+        };
+
+        let mut printed = false;
+        for err in &errors {
+            match err.kind() {
+                ParseErrorKind::UnknownParameter { argument, .. } => {
+                    if self.print_unknown_parameters {
+                        println!("Unknown parameter: {}", argument);
+                        if let Some(alias) = self.closest_alias(argument) {
+                            println!("Did you mean {}?", alias);
+                        }
+                        printed = true;
+                    }
+                },
+                ParseErrorKind::AmbiguousParameter { .. }
+                | ParseErrorKind::InvalidShortFlag { .. }
+                | ParseErrorKind::UnexpectedPositional { .. }
+                | ParseErrorKind::UnexpectedValue { .. } => {
+                    println!("{}", err);
+                    printed = true;
+                },
+                err => panic!("{}", err),
+            }
+        }
+
+        if printed {
+            println!("{}", self.generate_usage_line());
+        }
+    }
+
+    /// Parses the given arguments in the same way as `try_parse_args`, without requiring them to be valid UTF-8.
+    /// See `parse_command_line_os`.
+    pub fn try_parse_args_os<I: IntoIterator<Item = OsString>>(&mut self, args: I) -> Result<(), Vec<ParseError>> {
+        let result = self.parse_arguments(args);
+        if result.is_err() {
+            self.parse_failed = true;
+        }
+        result
+    }
+
+    /// Reports what parsing the arguments would do without changing the `CommandLineProcessor` or printing anything.
+    /// Standard input is not read, so a `-` value of a parameter that allows it is reported as `-`.
+    /// 
+    /// The arguments are parsed in the same way as `try_parse_args`, so the report contains every error in the arguments.
+    pub fn analyze(&self, args: &[String]) -> ParseReport {
+        let mut processor = self.clone();
+        processor.reset_values();
+        processor.set_quiet();
+
+        let errors = processor.parse_arguments(args.iter().map(OsString::from)).err().unwrap_or_default();
+
+        let matched = processor.ordered_parameters().into_iter()
+            .chain(processor.positionals.iter())
+            .filter(|parameter| parameter.was_set)
+            .map(|parameter| match parameter.secret {
+                true => (parameter.parameter_name.clone(), ParameterValue::String(REDACTED.to_owned())),
+                false => (parameter.parameter_name.clone(), parameter.current_value().clone()),
+            })
+            .collect();
+
+        ParseReport {
+            matched,
+            unknown: processor.unknown_parameters.clone(),
+            errors,
+            info_requested: processor.info_printed,
+        }
+    }
+
+    /// Stops the `CommandLineProcessor` and its subcommands from printing the help and version text, and from reading
+    /// standard input for a `-` value, which is kept as the value instead.
+    fn set_quiet(&mut self) {
+        self.quiet = true;
+        for parameter in self.parameters.values_mut().chain(self.positionals.iter_mut()) {
+            parameter.stdin_disabled = true;
+        }
+        for (_, subcommand) in self.subcommands.iter_mut() {
+            subcommand.set_quiet();
+        }
+    }
+
+    /// Parses the given arguments. Called by `try_parse_args_os`, which records whether parsing failed.
+    /// 
+    /// Parameter names are matched against a lossy conversion of each argument to UTF-8, while path values taken
+    /// from separate arguments keep their original bytes.
+    fn parse_arguments<I: IntoIterator<Item = OsString>>(&mut self, args: I) -> Result<(), Vec<ParseError>> {
+        let mut args: Vec<OsString> = args.into_iter().collect();
+        self.warnings.truncate(self.setup_warnings);
+        self.no_args = args.is_empty();
+        if self.no_args && self.print_help_if_no_args {
+            self.print_help_text();
+            self.info_printed = true;
+            self.abort_flag = true;
+        }
+
+        if self.response_files {
+            args = expand_response_files(args, 0).map_err(|err| vec![err])?;
+        }
+
+        let mut errors = self.load_config_arguments(&args);
+        let mut iter = args.into_iter().peekable();
+        let mut positional_index = 0;
+        let mut position = 0;
+        // Index of an argument already reported as the value of a flag, so it is not reported again as a positional.
+        let mut reported_value = None;
+        self.trace.clear();
+
+        while let Some(raw_argument) = iter.next() {
+            let index = position;
+            position += 1;
+            let argument = raw_argument.to_string_lossy().into_owned();
+
+            if argument == "--" {
+                self.trailing_args = iter.by_ref().map(|argument| argument.to_string_lossy().into_owned()).collect();
+                trace_parse!("Trailing arguments: {:?}", self.trailing_args);
+                break;
+            }
+
+            if self.is_builtin_alias(&self.help_aliases, &argument) {
+                trace_parse!("{} requests the help text", argument);
+                self.trace.push((argument, "help".to_owned()));
+                self.print_help_text();
+                self.info_printed = true;
+                self.abort_flag = true;
+                continue;
+            }
+
+            if self.is_builtin_alias(&self.version_aliases, &argument) {
+                trace_parse!("{} requests the version text", argument);
+                self.trace.push((argument, "version".to_owned()));
+                self.print_version_text();
+                self.info_printed = true;
+                self.abort_flag = true;
+                continue;
+            }
+
+            let arg: &str = &argument;
+
+            // Split `--key=value` at the first `=` into the parameter and its value.
+            let (name, inline_value) = match arg.find('=') {
+                Some(index) if arg.starts_with('-') => (&arg[..index], Some(arg[index + 1..].to_owned())),
+                _ => (arg, None),
+            };
+
+            // A negative number is a value rather than combined flags, unless digits are registered as short aliases.
+            let negative_number = arg.starts_with('-') && arg[1..].starts_with(|c: char| c.is_ascii_digit() || c == '.')
+                && f64::from_str(arg).is_ok() && !self.has_digit_short_alias();
+            let is_subcommand = positional_index == 0 && self.subcommands.iter().any(|(subcommand_name, _)| subcommand_name == arg);
+            let positional_available = positional_index < self.positionals.len();
+            // With no parameters registered, combined flags can't match and are treated as unknown parameters.
+            let combined_flags = arg.len() > 2 && arg.starts_with('-') && !arg.starts_with("--") && !self.parameters.is_empty() && !negative_number;
+
+            let resolved = match self.resolve_alias(name, index) {
+                Ok(resolved) => resolved,
+                Err(err) => {
+                    self.abort_flag = true;
+                    errors.push(err);
+                    continue;
+                },
+            };
+
+            if let Some((parameter_name, _)) = &resolved {
+                trace_parse!("Argument {} ({}) matches parameter {}", index, name, parameter_name);
+                let secret = self.parameters.get(parameter_name).is_some_and(|parameter| parameter.secret);
+                let token = match inline_value {
+                    Some(_) if secret => format!("{}={}", name, REDACTED),
+                    _ => argument.clone(),
+                };
+                self.trace.push((token, parameter_name.clone()));
+                self.warn_if_deprecated(parameter_name, name);
+            }
+
+            let parameters = &mut self.parameters;
+            match resolved.and_then(|(parameter_name, negated)| parameters.get_mut(&parameter_name).map(|parameter| (parameter, negated))) {
+                Some((parameter, negated)) => {
+                    if !parameter.takes_value() {
+                        if inline_value.is_some() {
+                            self.abort_flag = true;
+                            errors.push(ParseError::new(ParseErrorKind::UnexpectedValue { flag: name.to_owned() }, Some(index)));
+                            continue;
+                        }
+
+                        // A boolean after a flag, as in `--verbose true`, was most likely meant as the flag's value, so
+                        // it is reported. The flag never consumes the next argument, which is parsed on its own.
+                        let next_is_bool = iter.peek().and_then(|next| next.to_str()).is_some_and(is_bool_literal);
+                        if next_is_bool && !positional_available {
+                            self.abort_flag = true;
+                            errors.push(ParseError::new(ParseErrorKind::UnexpectedValue { flag: name.to_owned() }, Some(index)));
+                            reported_value = Some(index + 1);
+                        }
+
+                        if negated {
+                            parameter.value = ParameterValue::Bool(false);
+                        } else {
+                            parameter.set_flag();
+                        }
+                        parameter.was_set = true;
+                        trace_parse!("Stored {} in parameter {}", shown_value(parameter), parameter.parameter_name);
+
+                        if let Some((callback, abort)) = parameter.on_set.clone() {
+                            if !self.quiet {
+                                callback();
+                            }
+                            if abort {
+                                self.info_printed = true;
+                                self.abort_flag = true;
+                                break;
+                            }
+                        }
+                        continue;
+                    }
+
+                    if parameter.greedy {
+                        let rest: Vec<String> = iter.by_ref().map(|argument| argument.to_string_lossy().into_owned()).collect();
+                        self.trace.extend(rest.iter().map(|value| (shown(parameter, value), parameter.parameter_name.clone())));
+                        let values: Vec<String> = inline_value.into_iter().chain(rest).collect();
+                        let value = match parameter.parameter_type {
+                            ParameterType::StringList => ParameterValue::StringList(values),
+                            _ => ParameterValue::String(values.join(" ")),
+                        };
+
+                        match validate_value(parameter, value) {
+                            Ok(value) => {
+                                parameter.value = value;
+                                parameter.was_set = true;
+                                trace_parse!("Stored {} in parameter {}", shown_value(parameter), parameter.parameter_name);
+                            },
+                            Err(err) => errors.push(err.at_index(index)),
+                        }
+                        break;
+                    }
+
+                    if let Some(count) = parameter.nargs {
+                        // An inline value is part of the parameter's own argument.
+                        let first_index = if inline_value.is_some() { index } else { index + 1 };
+                        let values: Vec<OsString> = inline_value.map(OsString::from).into_iter().chain(iter.by_ref()).take(count).collect();
+                        position = first_index + values.len();
+                        let separate = if first_index == index { 1 } else { 0 };
+                        self.trace.extend(values.iter().skip(separate).map(|value| (shown(parameter, &value.to_string_lossy()), parameter.parameter_name.clone())));
+                        if values.len() < count {
+                            errors.push(ParseError::new(ParseErrorKind::NotEnoughValues {
+                                parameter: parameter.parameter_name.clone(),
+                                expected: count,
+                                found: values.len(),
+                            }, Some(index)));
+                            continue;
+                        }
+
+                        for (value_index, value) in values.into_iter().enumerate() {
+                            match parse_os_value(parameter, value) {
+                                Ok(value) => parameter.store_value(value),
+                                Err(err) => errors.push(err.at_index(first_index + value_index)),
+                            }
+                        }
+                        parameter.was_set = true;
+                        trace_parse!("Stored {} in parameter {}", shown_value(parameter), parameter.parameter_name);
+                        continue;
+                    }
+
+                    // The value is always taken from the next argument, so values starting
+                    // with `-` (such as negative numbers) are not mistaken for parameters.
+                    let (value, value_index) = match inline_value {
+                        Some(value) => (OsString::from(value), index),
+                        None => match iter.next() {
+                            Some(value) => {
+                                position += 1;
+                                self.trace.push((shown(parameter, &value.to_string_lossy()), parameter.parameter_name.clone()));
+                                (value, index + 1)
+                            },
+                            None => {
+                                errors.push(ParseError::new(ParseErrorKind::MissingValue {
+                                    parameter: parameter.parameter_name.clone(),
+                                }, Some(index)));
+                                continue;
+                            },
+                        },
+                    };
+
+                    let value = match parse_os_value(parameter, value) {
+                        Ok(value) => value,
+                        Err(err) => {
+                            errors.push(err.at_index(value_index));
+                            continue;
+                        },
+                    };
+                    parameter.store_value(value);
+                    parameter.was_set = true;
+                    trace_parse!("Stored {} in parameter {}", shown_value(parameter), parameter.parameter_name);
+                },
+                None if combined_flags => {
+                    match self.set_combined_flags(arg, index) {
+                        Ok(true) => break,
+                        Ok(false) => (),
+                        // A token such as `-Wall` meant for another program is passed through like any unknown parameter.
+                        Err(ParseError { kind: ParseErrorKind::InvalidShortFlag { .. }, .. }) if self.mode == ParseMode::Permissive => {
+                            trace_parse!("Argument {} ({}) does not match any parameter", index, arg);
+                            self.trace.push((arg.to_owned(), String::new()));
+                            self.unknown_parameters.push(arg.to_owned());
+                        },
+                        Err(err) => {
+                            self.abort_flag = true;
+                            errors.push(err);
+                        },
+                    }
+                },
+                None if is_subcommand => {
+                    trace_parse!("Argument {} ({}) selects a subcommand", index, arg);
+                    self.trace.push((arg.to_owned(), arg.to_owned()));
+                    let rest: Vec<OsString> = iter.by_ref().collect();
+                    if let Err(subcommand_errors) = self.parse_subcommand(arg, rest) {
+                        errors.extend(subcommand_errors.into_iter().map(|err| err.offset_index(index + 1)));
+                    }
+                    break;
+                },
+                None if arg == "-" || negative_number || !arg.starts_with('-') => {
+                    match self.positionals.get_mut(positional_index) {
+                        Some(positional) => {
+                            let value = match parse_os_value(positional, raw_argument.clone()) {
+                                Ok(value) => value,
+                                Err(err) => {
+                                    errors.push(err.at_index(index));
+                                    continue;
+                                },
+                            };
+                            positional.store_value(value);
+                            positional.was_set = true;
+                            self.trace.push((arg.to_owned(), positional.parameter_name.clone()));
+                            trace_parse!("Stored {} in positional parameter {}", shown_value(positional), positional.parameter_name);
+
+                            match positional.parameter_type {
+                                ParameterType::PathList | ParameterType::StringList => (),
+                                _ => positional_index += 1,
+                            }
+                        },
+                        None if reported_value == Some(index) => {
+                            trace_parse!("Argument {} ({}) was reported as the value of a flag", index, arg);
+                            self.trace.push((arg.to_owned(), String::new()));
+                        },
+                        None => {
+                            self.abort_flag = true;
+                            errors.push(ParseError::new(ParseErrorKind::UnexpectedPositional {
+                                argument: arg.to_owned(),
+                            }, Some(index)));
+                        },
+                    }
+                },
+                None => {
+                    trace_parse!("Argument {} ({}) does not match any parameter", index, arg);
+                    self.trace.push((arg.to_owned(), String::new()));
+                    self.unknown_parameters.push(arg.to_owned());
+                    if self.mode == ParseMode::Strict {
+                        self.abort_flag = true;
+                        errors.push(ParseError::new(ParseErrorKind::UnknownParameter {
+                            argument: arg.to_owned(),
+                        }, Some(index)));
+                    }
+                },
+            }
+        }
+
+        errors.extend(self.apply_env_fallbacks());
+
+        if self.prompt_on_missing && !self.abort_flag && errors.is_empty() {
+            errors.extend(self.prompt_for_missing());
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Makes a path parameter, such as `--config`, name a config file that is loaded before the other arguments are
+    /// parsed, wherever it appears in the arguments. Values from the file are overridden by the other arguments.
+    /// 
+    /// The file is loaded with `load_from_file`, or with `load_defaults_from_toml` if it has a `.toml` extension and
+    /// the `toml` feature is enabled. Its values override values loaded by calling those methods directly, and are
+    /// replaced on the next parse. The parameter must be given by one of its aliases in full, as in
+    /// `--config app.conf` or `--config=app.conf`, before any subcommand.
+    /// 
+    /// # Panics
+    /// Panics if the parameter is not a `ParameterType::Path` or `ParameterType::ExistingPath` parameter.
+    pub fn set_config_parameter(&mut self, parameter_name: &str) {
+        if let Some(parameter) = self.parameters.get(parameter_name) {
+            match parameter.parameter_type {
+                ParameterType::Path | ParameterType::ExistingPath => (),
+                _ => panic!("Parameter {} must be a path parameter to name a config file", parameter_name),
+            }
+            self.config_parameter = Some(parameter_name.to_owned());
+        }
+    }
+
+    /// Loads the config files named by the config parameter in the arguments, returning an error for each file
+    /// that could not be loaded.
+    fn load_config_arguments(&mut self, args: &[OsString]) -> Vec<ParseError> {
+        let aliases = match self.config_parameter.as_ref().and_then(|name| self.parameters.get(name)) {
+            Some(parameter) => parameter.aliases.clone(),
+            None => return Vec::new(),
+        };
+
+        // Values from the file named in the last parse are replaced, not added to.
+        for parameter in self.parameters.values_mut() {
+            parameter.config_value = ParameterValue::None;
+        }
+
+        let mut paths = Vec::new();
+        let mut positional_seen = false;
+        let mut iter = args.iter();
+        while let Some(argument) = iter.next() {
+            let argument = argument.to_string_lossy();
+            if argument == "--" {
+                break;
+            }
+
+            if aliases.iter().any(|alias| *alias == argument) {
+                paths.extend(iter.next().map(PathBuf::from));
+                continue;
+            } else if let Some(path) = aliases.iter().find_map(|alias| argument.strip_prefix(alias.as_str()).and_then(|rest| rest.strip_prefix('='))) {
+                paths.push(PathBuf::from(path));
+                continue;
+            }
+
+            // The arguments after a subcommand belong to the subcommand.
+            if argument == "-" || !argument.starts_with('-') {
+                if !positional_seen && self.subcommands.iter().any(|(subcommand_name, _)| *subcommand_name == argument) {
+                    break;
+                }
+                positional_seen = true;
+                continue;
+            }
+
+            // The values of other parameters are skipped, so a value such as `--name --config` is not taken as the
+            // config parameter.
+            if argument.contains('=') {
+                continue;
+            }
+            let parameter = match self.resolve_alias(&argument, 0) {
+                Ok(Some((parameter_name, false))) => self.parameters.get(&parameter_name),
+                _ => None,
+            };
+            match parameter {
+                Some(parameter) if parameter.greedy => break,
+                Some(parameter) if parameter.takes_value() => {
+                    for _ in 0..parameter.nargs.unwrap_or(1) {
+                        iter.next();
+                    }
+                },
+                _ => (),
+            }
+        }
+
+        let mut errors = Vec::new();
+        for path in paths {
+            if let Err(err) = self.load_config_file(&path) {
+                errors.push(ParseError::new(ParseErrorKind::ConfigFileFailed { path, source: Box::new(err) }, None));
+            }
+        }
+        errors
+    }
+
+    /// Loads a config file named by the config parameter.
+    fn load_config_file(&mut self, path: &Path) -> Result<(), ConfigError> {
+        #[cfg(feature = "toml")]
+        {
+            if path.extension().is_some_and(|extension| extension == "toml") {
+                return self.read_toml_defaults(path, true);
+            }
+        }
+
+        self.read_config_file(path, true)
+    }
+
+    /// Loads parameter values from a config file of `key = value` lines, where each key is a parameter name.
+    /// 
+    /// Values are parsed in the same way as command line values and are overridden by values from the command line or
+    /// environment variables. Flags accept a boolean value. Blank lines and lines starting with `#` are ignored,
+    /// and a value may be wrapped in double quotes. Keys that don't match a parameter are added to `warnings`.
+    pub fn load_from_file(&mut self, path: &Path) -> Result<(), ConfigError> {
+        self.read_config_file(path, false)
+    }
+
+    /// Reads a config file of `key = value` lines into the values from `load_from_file`, or into the values from the
+    /// config parameter's file if `from_config_parameter` is true.
+    fn read_config_file(&mut self, path: &Path, from_config_parameter: bool) -> Result<(), ConfigError> {
+        let contents = fs::read_to_string(path)?;
+
+        for (index, line) in contents.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = match line.find('=') {
+                Some(index) => (line[..index].trim(), line[index + 1..].trim()),
+                None => return Err(ConfigError::Syntax { line: line_number }),
+            };
+            let value = if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+                &value[1..value.len() - 1]
+            } else {
+                value
+            };
+
+            let parameter = match self.parameters.get_mut(key) {
+                Some(parameter) => parameter,
+                None => {
+                    self.add_config_warning(format!("Unknown key {} on line {} of config file {}", key, line_number, path.display()), from_config_parameter);
+                    continue;
+                },
+            };
+
+            let value = match parse_config_value(parameter, value) {
+                Ok(value) => value,
+                Err(err) => return Err(ConfigError::Value { line: line_number, source: err }),
+            };
+
+            merge_value(parameter.config_layer(from_config_parameter), value);
+        }
+
+        Ok(())
+    }
+
+    /// Adds a warning that is kept across parses, such as one about how a parameter was added.
+    fn add_setup_warning(&mut self, message: String) {
+        self.warnings.insert(self.setup_warnings, message);
+        self.setup_warnings += 1;
+    }
+
+    /// Adds a warning about a config file. Warnings about the config parameter's file are replaced on each parse.
+    fn add_config_warning(&mut self, message: String, from_config_parameter: bool) {
+        if from_config_parameter {
+            self.warnings.push(message);
+        } else {
+            self.add_setup_warning(message);
+        }
+    }
+
+    /// Loads parameter values from the `[defaults]` table of a TOML file, where each key is a parameter name.
+    /// 
+    /// Values are used in the same way as values from `load_from_file`. Integers are accepted by number parameters,
+    /// floats by `ParameterType::Float`, booleans by flags and `ParameterType::Bool`, and strings by text and path
+    /// parameters. List parameters also accept an array. Any other TOML type is a `ConfigError::TypeMismatch`.
+    /// Keys that don't match a parameter are added to `warnings`.
+    #[cfg(feature = "toml")]
+    pub fn load_defaults_from_toml(&mut self, path: &Path) -> Result<(), ConfigError> {
+        self.read_toml_defaults(path, false)
+    }
+
+    /// Reads the `[defaults]` table of a TOML file in the same way as `read_config_file`.
+    #[cfg(feature = "toml")]
+    fn read_toml_defaults(&mut self, path: &Path, from_config_parameter: bool) -> Result<(), ConfigError> {
+        let contents = fs::read_to_string(path)?;
+        let table = contents.parse::<toml::Table>().map_err(ConfigError::Toml)?;
+        let defaults = match table.get("defaults") {
+            Some(toml::Value::Table(defaults)) => defaults,
+            _ => return Ok(()),
+        };
+
+        for (key, value) in defaults {
+            let parameter = match self.parameters.get_mut(key) {
+                Some(parameter) => parameter,
+                None => {
+                    self.add_config_warning(format!("Unknown key {} in config file {}", key, path.display()), from_config_parameter);
+                    continue;
+                },
+            };
+
+            let values: Vec<&toml::Value> = match (&parameter.parameter_type, value) {
+                (ParameterType::PathList, toml::Value::Array(values)) | (ParameterType::StringList, toml::Value::Array(values)) => values.iter().collect(),
+                _ => vec![value],
+            };
+
+            for value in values {
+                let text = match toml_text(parameter, value) {
+                    Some(text) => text,
+                    None => return Err(ConfigError::TypeMismatch { key: key.clone(), expected: parameter.parameter_type.clone() }),
+                };
+
+                let value = match parse_config_value(parameter, &text) {
+                    Ok(value) => value,
+                    Err(err) => return Err(ConfigError::InvalidValue { key: key.clone(), source: err }),
+                };
+
+                merge_value(parameter.config_layer(from_config_parameter), value);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Asks for the value of each required parameter that has no value, if stdin is a terminal, and returns the
+    /// errors from parsing the responses. An empty response leaves the parameter unset. A secret parameter is not
+    /// asked for if terminal echo can't be turned off; a warning is added instead.
+    fn prompt_for_missing(&mut self) -> Vec<ParseError> {
+        let mut errors = Vec::new();
+        if self.quiet || !io::stdin().is_terminal() {
+            return errors;
+        }
+
+        for parameter_name in self.parameter_order.clone() {
+            let parameter = match self.parameters.get_mut(&parameter_name) {
+                Some(parameter) if parameter.required && parameter.takes_value() => parameter,
+                _ => continue,
+            };
+
+            if !matches!(parameter.current_value(), ParameterValue::None) {
+                continue;
+            }
+
+            if parameter.secret && !set_terminal_echo(false) {
+                let message = format!("Parameter {} was not asked for because terminal echo can't be turned off", parameter_name);
+                if self.print_warnings {
+                    eprintln!("{}", message);
+                }
+                self.warnings.push(message);
+                continue;
+            }
+
+            let prompt = parameter.description.as_ref().unwrap_or(&parameter.parameter_name);
+            eprint!("{}: ", prompt);
+            let _ = io::stderr().flush();
+
+            let mut response = String::new();
+            let result = io::stdin().lock().read_line(&mut response);
+            if parameter.secret {
+                set_terminal_echo(true);
+                eprintln!();
+            }
+            if result.is_err() {
+                continue;
+            }
+
+            let response = response.trim_end_matches(['\n', '\r']);
+            if response.is_empty() {
+                continue;
+            }
+
+            match parse_value(parameter, response.to_owned()) {
+                Ok(value) => {
+                    parameter.store_value(value);
+                    parameter.prompted = true;
+                },
+                Err(err) => errors.push(err),
+            }
+        }
+
+        errors
+    }
+
+    /// Adds a warning if the parameter is deprecated, printing it if `set_print_warnings` is enabled.
+    fn warn_if_deprecated(&mut self, parameter_name: &str, argument: &str) {
+        let message = match self.parameters.get(parameter_name).and_then(|parameter| parameter.deprecated.as_ref()) {
+            Some(message) => format!("Parameter {} is deprecated: {}", argument, message),
+            None => return,
+        };
+
+        if self.print_warnings && !self.quiet {
+            eprintln!("{}", message);
+        }
+        self.warnings.push(message);
+    }
+
+    /// Selects a subcommand and parses the remaining arguments with it.
+    fn parse_subcommand(&mut self, subcommand_name: &str, args: Vec<OsString>) -> Result<(), Vec<ParseError>> {
+        let mut result = Ok(());
+
+        if let Some((name, subcommand)) = self.subcommands.iter_mut().find(|(name, _)| name == subcommand_name) {
+            self.selected_subcommand = Some(name.clone());
+            result = subcommand.try_parse_args_os(args);
+            if subcommand.abort_flag() {
+                self.abort_flag = true;
+            }
+            if subcommand.info_printed {
+                self.info_printed = true;
+            }
+        }
+
+        result
+    }
+
+    /// Sets parameters that were not passed on the command line from their fallback environment variables,
+    /// returning an error for each variable with an invalid value.
+    fn apply_env_fallbacks(&mut self) -> Vec<ParseError> {
+        let mut errors = Vec::new();
+        for parameter_name in self.parameter_order.clone() {
+            let parameter = match self.parameters.get_mut(&parameter_name) {
+                Some(parameter) if matches!(parameter.value, ParameterValue::None) => parameter,
+                _ => continue,
+            };
+
+            let (variable, value) = match &parameter.env_var {
+                Some(variable) => match env::var(variable) {
+                    Ok(value) => (variable.clone(), value),
+                    Err(_) => continue,
+                },
+                None => continue,
+            };
+
+            match parse_config_value(parameter, &value) {
+                Ok(value) => parameter.store_value(value),
+                Err(err) => errors.push(ParseError::new(ParseErrorKind::InvalidEnvValue {
+                    variable,
+                    source: Box::new(err),
+                }, None)),
+            }
+        }
+
+        errors
+    }
+
+    /// Returns true if a parameter has a short alias made of a digit, such as `-1`.
+    fn has_digit_short_alias(&self) -> bool {
+        self.parameters.values()
+            .flat_map(|parameter| parameter.aliases.iter())
+            .any(|alias| alias.len() == 2 && alias.starts_with('-') && alias[1..].starts_with(|c: char| c.is_ascii_digit()))
+    }
+
+    /// Returns the name of the parameter matching the alias. The second value is true if the alias is the `--no-` form of a negatable flag.
+    /// 
+    /// An exact match is preferred over a case-insensitive match, which is preferred over an abbreviation.
+    fn resolve_alias(&self, alias: &str, index: usize) -> Result<Option<(String, bool)>, ParseError> {
+        if let Some(parameter) = self.find_by_alias(alias) {
+            return Ok(Some((parameter.parameter_name.clone(), false)));
+        }
+
+        let lowercase_alias = alias.to_lowercase();
+        let folded_alias = if self.case_insensitive { &lowercase_alias } else { alias };
+        if let Some(name) = folded_alias.strip_prefix("--no-") {
+            if let Some(parameter) = self.find_by_alias(&format!("--{}", name)) {
+                if parameter.negatable {
+                    return Ok(Some((parameter.parameter_name.clone(), true)));
+                }
+            }
+        }
+
+        if self.allow_abbreviations && folded_alias.starts_with("--") && folded_alias.len() > 2 {
+            let mut candidates: Vec<(&str, &str)> = Vec::new();
+            for parameter in self.ordered_parameters() {
+                let matching_alias = parameter.aliases.iter().find(|x| {
+                    let x = if self.case_insensitive { x.to_lowercase() } else { x.to_string() };
+                    x.starts_with("--") && x.starts_with(folded_alias)
+                });
+
+                if let Some(matching_alias) = matching_alias {
+                    candidates.push((&parameter.parameter_name, matching_alias));
+                }
+            }
+
+            match candidates.len() {
+                0 => (),
+                1 => return Ok(Some((candidates[0].0.to_owned(), false))),
+                _ => return Err(ParseError::new(ParseErrorKind::AmbiguousParameter {
+                    argument: alias.to_owned(),
+                    candidates: candidates.iter().map(|&(_, matching_alias)| matching_alias.to_owned()).collect(),
+                }, Some(index))),
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Returns the parameter with the alias, ignoring case if case-insensitive matching is enabled.
+    fn find_by_alias(&self, alias: &str) -> Option<&Parameter> {
+        if let Some(parameter) = self.parameters.values().find(|parameter| parameter.aliases.iter().any(|x| x == alias)) {
+            return Some(parameter);
+        }
+
+        if self.case_insensitive {
+            let alias = alias.to_lowercase();
+            return self.ordered_parameters().into_iter()
+                .find(|parameter| parameter.aliases.iter().any(|x| x.to_lowercase() == alias));
+        }
+
+        None
+    }
+
+    /// Sets every flag in a combined short flag argument such as `-abc`. No flags are set if any character is not a flag.
+    /// A counter parameter is incremented each time its character appears.
+    /// 
+    /// If a character belongs to a parameter that takes a value, the rest of the argument is its value,
+    /// so `-n5` is the same as `-n 5` and `-vn5` also sets the `-v` flag.
+    /// 
+    /// Returns true if a function set with `set_on_set` stopped parsing.
+    fn set_combined_flags(&mut self, argument: &str, index: usize) -> Result<bool, ParseError> {
+        let mut names = Vec::new();
+        let mut callbacks = Vec::new();
+        let mut attached = None;
+        let mut token = argument.to_owned();
+
+        for (offset, flag) in argument.char_indices().skip(1) {
+            let alias = format!("-{}", flag);
+            match self.parameters.values().find(|parameter| parameter.aliases.contains(&alias)) {
+                Some(parameter) if !parameter.takes_value() => names.push((parameter.parameter_name.clone(), alias)),
+                Some(parameter) => {
+                    let value = &argument[offset + flag.len_utf8()..];
+                    if value.is_empty() {
+                        return Err(ParseError::new(ParseErrorKind::MissingValue {
+                            parameter: parameter.parameter_name.clone(),
+                        }, Some(index)));
+                    }
+                    let value = parse_value(parameter, value.to_owned()).map_err(|err| err.at_index(index))?;
+                    if parameter.secret {
+                        token = format!("{}{}", &argument[..offset + flag.len_utf8()], REDACTED);
+                    }
+                    attached = Some((parameter.parameter_name.clone(), alias, value));
+                    break;
+                },
+                None => return Err(ParseError::new(ParseErrorKind::InvalidShortFlag {
+                    argument: argument.to_owned(),
+                    flag,
+                }, Some(index))),
+            }
+        }
+
+        for (name, alias) in names {
+            if let Some(parameter) = self.parameters.get_mut(&name) {
+                parameter.set_flag();
+                parameter.was_set = true;
+                trace_parse!("{} in {} sets flag {}", alias, token, name);
+                callbacks.extend(parameter.on_set.clone());
+            }
+            self.warn_if_deprecated(&name, &alias);
+            self.trace.push((token.clone(), name));
+        }
+
+        if let Some((name, alias, value)) = attached {
+            if let Some(parameter) = self.parameters.get_mut(&name) {
+                parameter.store_value(value);
+                parameter.was_set = true;
+                trace_parse!("{} in {} stored {} in parameter {}", alias, token, shown_value(parameter), name);
+            }
+            self.warn_if_deprecated(&name, &alias);
+            self.trace.push((token, name));
+        }
+
+        for (callback, abort) in callbacks {
+            if !self.quiet {
+                callback();
+            }
+            if abort {
+                self.info_printed = true;
+                self.abort_flag = true;
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Sets the text to print when the `--help` parameter is used.
+    pub fn set_help_text(&mut self, help_text: &str) {
+        self.help_text = Some(help_text.to_owned());
+    }
+
+    /// Returns true if the argument is one of the built-in help or version aliases and the built-in flags are enabled.
+    /// A parameter registered with `-h` or `-V` keeps that alias, as it did before they became defaults.
+    fn is_builtin_alias(&self, aliases: &[String], argument: &str) -> bool {
+        let claimed = SHORT_BUILTIN_ALIASES.contains(&argument)
+            && self.parameters.values().any(|parameter| parameter.aliases.iter().any(|alias| alias == argument));
+        self.builtin_flags_enabled && aliases.iter().any(|alias| alias == argument) && !claimed
+    }
+
+    /// Sets the aliases that print the help text. Defaults to `--help`, `-h` and `--h`.
+    /// A parameter registered with `-h` takes precedence over the built-in alias.
+    pub fn set_help_aliases<A: IntoIterator>(&mut self, aliases: A) where A::Item: AsRef<str> {
+        self.help_aliases = aliases.into_iter().map(|alias| alias.as_ref().to_owned()).collect();
+    }
+
+    /// Sets the aliases that print the version text. Defaults to `--version`, `-V` and `--v`.
+    /// A parameter registered with `-V` takes precedence over the built-in alias.
+    pub fn set_version_aliases<A: IntoIterator>(&mut self, aliases: A) where A::Item: AsRef<str> {
+        self.version_aliases = aliases.into_iter().map(|alias| alias.as_ref().to_owned()).collect();
+    }
+
+    /// Enables or disables the built-in help and version handling. Enabled by default.
+    /// 
+    /// When disabled, the help and version aliases are treated like any other argument so they can be added as parameters.
+    pub fn set_builtin_flags_enabled(&mut self, enabled: bool) {
+        self.builtin_flags_enabled = enabled;
+    }
+
+    /// Sets whether the help text is printed and the abort flag set when the program is run without arguments.
+    /// Disabled by default.
+    pub fn set_print_help_if_no_args(&mut self, enabled: bool) {
+        self.print_help_if_no_args = enabled;
+    }
+
+    /// Sets whether aliases are matched ignoring case, so `--VERBOSE` matches `--verbose`. Disabled by default.
+    /// 
+    /// Only the alias is compared ignoring case; values, including the value in `--key=VALUE`, are kept as written.
+    /// An alias that matches exactly is always preferred, and the help and version aliases are always matched exactly.
+    pub fn set_case_insensitive(&mut self, enabled: bool) {
+        self.case_insensitive = enabled;
+    }
+
+    /// Sets whether a long alias can be abbreviated, so `--verb` matches `--verbose` if no other long alias starts with `--verb`.
+    /// Disabled by default. An abbreviation that matches more than one parameter is reported as `ParseErrorKind::AmbiguousParameter`.
+    pub fn set_allow_abbreviations(&mut self, enabled: bool) {
+        self.allow_abbreviations = enabled;
+    }
+
+    /// Sets whether a short alias such as `-v` is added for each long alias such as `--verbose`. Disabled by default.
+    /// 
+    /// Enabling this adds short aliases to the parameters that have already been added and to each parameter added
+    /// afterwards. A short alias is only added if no other parameter or the help and version text uses it, and
+    /// each one that can't be added because of a conflict is reported in `warnings`.
+    pub fn set_auto_short_aliases(&mut self, enabled: bool) {
+        self.auto_short_aliases = enabled;
+        if enabled {
+            for name in self.parameter_order.clone() {
+                self.add_short_aliases(&name);
+            }
+        }
+    }
+
+    /// Sets whether an argument such as `@args.txt` is replaced by the arguments in that file. Disabled by default.
+    /// 
+    /// The file's arguments are separated by whitespace and may be wrapped in single or double quotes to include
+    /// whitespace. A response file can refer to other response files up to 10 levels deep. Arguments after a
+    /// standalone `--` are not expanded, and error indexes count the arguments after expansion.
+    pub fn set_response_files(&mut self, enabled: bool) {
+        self.response_files = enabled;
+    }
+
+    /// Prints the help text. Prints the generated help text if the help text is not set.
+    fn print_help_text(&self) {
+        if self.quiet {
+            return;
+        }
+        let _ = self.write_help_text(&mut io::stdout());
+    }
+
+    /// Writes the help text to the given writer, or the generated help text if none has been set.
+    pub fn write_help_text(&self, w: &mut impl io::Write) -> io::Result<()> {
+        match &self.help_text {
+            Some(help_text) => writeln!(w, "{}", help_text),
+            None => writeln!(w, "{}", self.generate_help_text()),
+        }
+    }
+
+    /// Sets the program name shown in the usage line of the generated help text.
+    /// Defaults to the file name of the executable.
+    pub fn set_program_name(&mut self, name: &str) {
+        self.program_name = Some(name.to_owned());
+    }
+
+    /// Sets a one-line description of the program shown below the usage line of the generated help text.
+    pub fn set_about(&mut self, about: &str) {
+        self.about = Some(about.to_owned());
+    }
+
+    /// Adds a section, such as "Examples" or "Environment", to the end of the generated help text.
+    /// Sections are shown in the order they were added, with each line of the body indented under the title.
+    pub fn add_help_section(&mut self, title: &str, body: &str) {
+        self.help_sections.push((title.to_owned(), body.to_owned()));
+    }
+
+    /// Sets the width that descriptions in the generated help text are wrapped to.
+    /// Defaults to the `COLUMNS` environment variable, or 80 columns if it isn't set.
+    pub fn set_help_width(&mut self, width: usize) {
+        self.help_width = Some(width);
+    }
+
+    /// Returns the width to wrap the generated help text to.
+    fn help_width(&self) -> usize {
+        match self.help_width {
+            Some(width) => width,
+            None => env::var("COLUMNS").ok()
+                .and_then(|columns| columns.trim().parse().ok())
+                .unwrap_or(80),
+        }
+    }
+
+    /// Returns the program name, or the file stem of the executable if none has been set.
+    fn program_name(&self) -> String {
+        match &self.program_name {
+            Some(name) => name.clone(),
+            None => env::args().next()
+                .and_then(|executable| Path::new(&executable).file_stem().map(|stem| stem.to_string_lossy().into_owned()))
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Returns a one-line synopsis such as `Usage: tool [--count <uint>] [--verbose] [file]`, listing each visible
+    /// parameter by its first alias and value placeholder. Parameters that are not required are shown in brackets,
+    /// followed by the positional arguments and `[COMMAND]` if there are subcommands.
+    pub fn generate_usage_line(&self) -> String {
+        let mut usage = format!("Usage: {}", self.program_name());
+
+        for parameter in self.visible_parameters() {
+            let mut option = parameter.aliases.first().cloned().unwrap_or_default();
+            if let Some(placeholder) = parameter.placeholder() {
+                option.push(' ');
+                option.push_str(&placeholder);
+            }
+
+            match parameter.required {
+                true => usage.push_str(&format!(" {}", option)),
+                false => usage.push_str(&format!(" [{}]", option)),
+            }
+        }
+
+        for positional in &self.positionals {
+            match positional.parameter_type {
+                ParameterType::PathList | ParameterType::StringList => usage.push_str(&format!(" [{}...]", positional.parameter_name)),
+                _ => usage.push_str(&format!(" [{}]", positional.parameter_name)),
+            }
+        }
+
+        if !self.subcommands.is_empty() {
+            usage.push_str(" [COMMAND]");
+        }
+
+        usage
+    }
+
+    /// Returns help text starting with a usage line and the about text, followed by each registered parameter's
+    /// aliases, value type and description. Ungrouped parameters are listed under "Options" and grouped parameters
+    /// under their group's heading. Sections added with `add_help_section` come last.
+    pub fn generate_help_text(&self) -> String {
+        let parameters = self.visible_parameters();
+
+        let options: Vec<String> = parameters.iter()
+            .map(|parameter| {
+                // Short aliases come before long ones, as in `-v, --verbose`, whatever order they were added in.
+                let mut aliases = parameter.aliases.clone();
+                aliases.sort_by_key(|alias| (alias.chars().take_while(|c| *c == '-').count(), alias.len()));
+                aliases.extend(parameter.negated_aliases());
+                let mut option = aliases.join(", ");
+                if let Some(placeholder) = parameter.placeholder() {
+                    option.push(' ');
+                    option.push_str(&placeholder);
+                }
+                option
+            })
+            .collect();
+        let width = options.iter().map(|option| option.len()).max().unwrap_or(0);
+        // Descriptions start after the indent, the widest option and a gap, and wrap to at least 20 columns.
+        let description_indent = 4 + width + 4;
+        let description_width = self.help_width().saturating_sub(description_indent).max(20);
+
+        let mut help_text = format!("Usage: {} [OPTIONS]", self.program_name());
+        if !self.subcommands.is_empty() {
+            help_text.push_str(" [COMMAND]");
+        }
+        if let Some(about) = &self.about {
+            help_text.push_str("\n\n");
+            help_text.push_str(about);
+        }
+
+        if parameters.is_empty() && self.subcommands.is_empty() {
+            help_text.push_str("\n\nThis program takes no options.");
+        }
+
+        let groups = Some(None).into_iter().chain(self.help_groups.iter().map(Some));
+        for group in groups {
+            let mut entries = parameters.iter()
+                .zip(options.iter())
+                .filter(|(parameter, _)| parameter.group.as_ref() == group)
+                .peekable();
+            if entries.peek().is_none() {
+                continue;
+            }
+
+            help_text.push_str("\n\n");
+            help_text.push_str(group.map_or("Options", |group| group.as_str()));
+            help_text.push(':');
+            for (parameter, option) in entries {
+                help_text.push_str("\n    ");
+                match &parameter.description {
+                    Some(description) => {
+                        let lines = wrap_text(description, description_width);
+                        let separator = format!("\n{}", " ".repeat(description_indent));
+                        help_text.push_str(&format!("{:width$}    {}", option, lines.join(&separator), width = width));
+                    },
+                    None => help_text.push_str(option),
+                }
+            }
+        }
+
+        if !self.subcommands.is_empty() {
+            help_text.push_str("\n\nCommands:");
+            for (name, _) in &self.subcommands {
+                help_text.push_str("\n    ");
+                help_text.push_str(name);
+            }
+        }
+
+        for (title, body) in &self.help_sections {
+            help_text.push_str("\n\n");
+            help_text.push_str(title);
+            help_text.push(':');
+            for line in body.lines() {
+                help_text.push('\n');
+                if !line.is_empty() {
+                    help_text.push_str("    ");
+                    help_text.push_str(line);
+                }
+            }
+        }
+
+        help_text
+    }
+
+    /// Returns a bash completion script for the program that can be loaded with `source`.
+    /// 
+    /// Every alias is offered as a completion, and file or directory names are offered for path parameters.
+    pub fn generate_bash_completion(&self, program_name: &str) -> String {
+        let function_name: String = program_name.chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+
+        let mut words: Vec<&str> = Vec::new();
+        if self.builtin_flags_enabled {
+            words.extend(self.help_aliases.iter().map(|alias| alias.as_str()));
+            words.extend(self.version_aliases.iter().map(|alias| alias.as_str()));
+        }
+
+        let mut cases = String::new();
+        for parameter in self.visible_parameters() {
+            words.extend(parameter.aliases.iter().map(|alias| alias.as_str()));
+
+            let completion = match (&parameter.parameter_type, &parameter.choices) {
+                (_, Some(choices)) => format!("compgen -W \"{}\" -- \"${{cur}}\"", choices.join(" ")),
+                (ParameterType::Path, _) | (ParameterType::PathList, _) | (ParameterType::ExistingPath, _) => "compgen -f -- \"${cur}\"".to_owned(),
+                (ParameterType::ExistingDir, _) => "compgen -d -- \"${cur}\"".to_owned(),
+                _ => continue,
+            };
+
+            if parameter.aliases.is_empty() {
+                continue;
+            }
+
+            cases.push_str(&format!("        {})\n", parameter.aliases.join("|")));
+            cases.push_str(&format!("            COMPREPLY=( $({}) )\n", completion));
+            cases.push_str("            return 0\n");
+            cases.push_str("            ;;\n");
+        }
+
+        let mut script = format!("_{}() {{\n", function_name);
+        script.push_str("    local cur prev\n");
+        script.push_str("    COMPREPLY=()\n");
+        script.push_str("    cur=\"${COMP_WORDS[COMP_CWORD]}\"\n");
+        script.push_str("    prev=\"${COMP_WORDS[COMP_CWORD-1]}\"\n");
+        script.push('\n');
+        if !cases.is_empty() {
+            script.push_str("    case \"${prev}\" in\n");
+            script.push_str(&cases);
+            script.push_str("    esac\n");
+            script.push('\n');
+        }
+        script.push_str(&format!("    COMPREPLY=( $(compgen -W \"{}\" -- \"${{cur}}\") )\n", words.join(" ")));
+        script.push_str("    return 0\n");
+        script.push_str("}\n");
+        script.push_str(&format!("complete -F _{} {}\n", function_name, program_name));
+
+        script
+    }
+
+    /// Returns a zsh completion script for the program using `_arguments`.
+    /// 
+    /// Each alias is completed with the parameter's description, and file or directory names are offered for path parameters.
+    pub fn generate_zsh_completion(&self, program_name: &str) -> String {
+        let mut specs = Vec::new();
+        if self.builtin_flags_enabled {
+            specs.push(zsh_spec(&self.help_aliases, "", Some("Print help text"), ""));
+            specs.push(zsh_spec(&self.version_aliases, "", Some("Print version text"), ""));
+        }
+
+        for parameter in self.visible_parameters() {
+            let repeat = match parameter.parameter_type {
+                ParameterType::PathList | ParameterType::StringList | ParameterType::Counter => "*",
+                _ => "",
+            };
+
+            let action = match (&parameter.parameter_type, &parameter.choices) {
+                (ParameterType::Flag, _) | (ParameterType::Counter, _) => String::new(),
+                (_, Some(choices)) => format!(":{}:({})", parameter.parameter_name, choices.join(" ")),
+                (ParameterType::Path, _) | (ParameterType::PathList, _) | (ParameterType::ExistingPath, _) => format!(":{}:_files", parameter.parameter_name),
+                (ParameterType::ExistingDir, _) => format!(":{}:_files -/", parameter.parameter_name),
+                _ => format!(":{}: ", parameter.parameter_name),
+            };
+
+            specs.push(zsh_spec(&parameter.aliases, repeat, parameter.description.as_deref(), &action));
+        }
+
+        let specs: Vec<String> = specs.into_iter().filter(|spec| !spec.is_empty()).collect();
+
+        let mut script = format!("#compdef {}\n\n_arguments", program_name);
+        for spec in specs {
+            script.push_str(" \\\n    ");
+            script.push_str(&spec);
+        }
+        script.push('\n');
+
+        script
+    }
+
+    /// Sets the text to print when the `--version` parameter is used.
+    pub fn set_version_text(&mut self, version_text: &str) {
+        self.version_text = Some(version_text.to_owned());
+    }
+
+    /// Prints the version text. Prints a default message if the version text is not set.
+    fn print_version_text(&self) {
+        if self.quiet {
+            return;
+        }
+        let _ = self.write_version_text(&mut io::stdout());
+    }
+
+    /// Writes the version text to the given writer.
+    pub fn write_version_text(&self, w: &mut impl io::Write) -> io::Result<()> {
+        match &self.version_text {
+            Some(version_text) => writeln!(w, "{}", version_text),
+            None => writeln!(w, "No version text has been set."),
+        }
+    }
+
+    /// Returns the `ParameterValue` for the specified parameter, or its default value if it has not been set.
+    /// Returns `ParameterValue::None` if the parameter doesn't exist.
+    /// 
+    /// The parameter can be given by name or by the `ParamKey` returned when it was added, as with the other getters.
+    pub fn get_parameter_value<K: AsRef<str> + ?Sized>(&self, parameter_name: &K) -> &ParameterValue {
+        let parameter_name = parameter_name.as_ref();
+        match self.parameters.get(parameter_name) {
+            Some(parameter) => parameter.current_value(),
+            None => &ParameterValue::None,
+        }
+    }
+
+    /// Returns the `ParameterValue` for the specified parameter, or its default value if it has not been set.
+    /// Returns `None` if the parameter doesn't exist, and `Some(&ParameterValue::None)` if it exists but is unset.
+    pub fn try_get_parameter_value<K: AsRef<str> + ?Sized>(&self, parameter_name: &K) -> Option<&ParameterValue> {
+        let parameter_name = parameter_name.as_ref();
+        self.parameters.get(parameter_name).map(|parameter| parameter.current_value())
+    }
+
+    /// Returns a listing of every parameter and positional argument, in the order they were added, with its current
+    /// value and where the value came from, such as `count: 5 (command line)` or `verbose: <unset>`.
+    /// 
+    /// The source is one of command line, prompt, environment, config file or default. Secret values are shown as `***`.
+    pub fn describe_state(&self) -> String {
+        let lines: Vec<String> = self.ordered_parameters().into_iter()
+            .chain(self.positionals.iter())
+            .map(|parameter| {
+                let source = if parameter.was_set {
+                    "command line"
+                } else if parameter.prompted {
+                    "prompt"
+                } else if !matches!(parameter.value, ParameterValue::None) {
+                    "environment"
+                } else if !matches!(parameter.config_value, ParameterValue::None) || !matches!(parameter.file_value, ParameterValue::None) {
+                    "config file"
+                } else if !matches!(parameter.default, ParameterValue::None) {
+                    "default"
+                } else {
+                    return format!("{}: <unset>", parameter.parameter_name);
+                };
+
+                let value = shown(parameter, &value_text(parameter.current_value()));
+                format!("{}: {} ({})", parameter.parameter_name, value, source)
+            })
+            .collect();
+
+        lines.join("\n")
+    }
+
+    /// Returns the current value of every parameter that is set or has a default value, keyed by parameter name.
+    /// The value of a secret parameter is returned as the string `***`.
+    pub fn values_snapshot(&self) -> HashMap<String, ParameterValue> {
+        self.parameters.values()
+            .filter(|parameter| !matches!(parameter.current_value(), ParameterValue::None))
+            .map(|parameter| match parameter.secret {
+                true => (parameter.parameter_name.clone(), ParameterValue::String(REDACTED.to_owned())),
+                false => (parameter.parameter_name.clone(), parameter.current_value().clone()),
+            })
+            .collect()
+    }
+
+    /// Returns a JSON object of every parameter that is set or has a default value, keyed by parameter name.
+    /// 
+    /// Numbers and booleans are written as JSON numbers and booleans, paths and text as strings, lists as arrays,
+    /// and set flags as `true`. The value of a secret parameter is written as `"***"`.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        let object: serde_json::Map<String, serde_json::Value> = self.values_snapshot().iter()
+            .map(|(name, value)| match self.parameters.get(name) {
+                Some(parameter) if parameter.secret => (name.clone(), serde_json::Value::String(REDACTED.to_owned())),
+                _ => (name.clone(), json_value(value)),
+            })
+            .collect();
+
+        serde_json::Value::Object(object).to_string()
+    }
+
+    /// Returns the `ParameterValue` for the specified positional argument.
+    /// Returns `ParameterValue::None` if the positional argument doesn't exist.
+    pub fn get_positional(&self, parameter_name: &str) -> &ParameterValue {
+        match self.positionals.iter().find(|positional| positional.parameter_name == parameter_name) {
+            Some(positional) => positional.current_value(),
+            None => &ParameterValue::None,
+        }
+    }
+
+    /// Returns true if the specified parameter is a flag that has been set. Returns false if a negatable flag was negated.
+    pub fn is_flag_set<K: AsRef<str> + ?Sized>(&self, parameter_name: &K) -> bool {
+        let parameter_name = parameter_name.as_ref();
+        match self.parameters.get(parameter_name) {
+            Some(parameter) if matches!(parameter.parameter_type, ParameterType::Flag) => {
+                matches!(parameter.current_value(), ParameterValue::Flag | ParameterValue::Bool(true))
+            },
+            _ => false,
+        }
+    }
+
+    /// Returns the value of a `ParameterType::Integer` parameter. Returns `None` if the parameter is unset or not an integer.
+    pub fn get_integer<K: AsRef<str> + ?Sized>(&self, parameter_name: &K) -> Option<i64> {
+        match self.get_parameter_value(parameter_name) {
+            ParameterValue::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a `ParameterType::UInteger` parameter. Returns `None` if the parameter is unset or not an unsigned integer.
+    pub fn get_uinteger<K: AsRef<str> + ?Sized>(&self, parameter_name: &K) -> Option<u32> {
+        match self.get_parameter_value(parameter_name) {
+            ParameterValue::UInteger(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a `ParameterType::ULong` parameter. Returns `None` if the parameter is unset or not a u64 value.
+    pub fn get_ulong<K: AsRef<str> + ?Sized>(&self, parameter_name: &K) -> Option<u64> {
+        match self.get_parameter_value(parameter_name) {
+            ParameterValue::ULong(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a `ParameterType::Path` parameter. Returns `None` if the parameter is unset or not a path.
+    pub fn get_path<K: AsRef<str> + ?Sized>(&self, parameter_name: &K) -> Option<&Path> {
+        match self.get_parameter_value(parameter_name) {
+            ParameterValue::Path(path) => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a `ParameterType::String` parameter. Returns `None` if the parameter is unset or not a string.
+    pub fn get_string<K: AsRef<str> + ?Sized>(&self, parameter_name: &K) -> Option<&str> {
+        match self.get_parameter_value(parameter_name) {
+            ParameterValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a `ParameterType::Float` parameter. Returns `None` if the parameter is unset or not a float.
+    pub fn get_float<K: AsRef<str> + ?Sized>(&self, parameter_name: &K) -> Option<f64> {
+        match self.get_parameter_value(parameter_name) {
+            ParameterValue::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Checks the parsed values against the constraints on the parameters. Returns every constraint that is not met.
+    /// 
+    /// Every required parameter must have a value or a default value, at most one parameter of each exclusive group
+    /// may be passed, and each parameter that is passed must have the parameters it requires.
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let mut errors: Vec<ValidationError> = self.ordered_parameters().into_iter()
+            .filter(|parameter| parameter.required)
+            .filter(|parameter| matches!(parameter.current_value(), ParameterValue::None))
+            .map(|parameter| ValidationError::MissingRequired(parameter.parameter_name.clone()))
+            .collect();
+
+        for group in &self.exclusive_groups {
+            let provided: Vec<String> = group.iter()
+                .filter(|name| match self.parameters.get(name.as_str()) {
+                    Some(parameter) => parameter.was_set,
+                    None => false,
+                })
+                .cloned()
+                .collect();
+
+            if provided.len() > 1 {
+                errors.push(ValidationError::Conflict(provided));
+            }
+        }
+
+        for (name, requires) in &self.requirements {
+            let provided = match self.parameters.get(name) {
+                Some(parameter) => parameter.was_set,
+                None => false,
+            };
+            if !provided {
+                continue;
+            }
+
+            for required in requires {
+                if matches!(self.get_parameter_value(required), ParameterValue::None) {
+                    errors.push(ValidationError::MissingDependency {
+                        parameter: name.clone(),
+                        requires: required.clone(),
+                    });
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Returns the paths of a `ParameterType::PathList` parameter. Returns an empty slice if the parameter is unset or not a path list.
+    pub fn get_path_list<K: AsRef<str> + ?Sized>(&self, parameter_name: &K) -> &[PathBuf] {
+        match self.get_parameter_value(parameter_name) {
+            ParameterValue::PathList(paths) => paths,
+            _ => &[],
+        }
+    }
+
+    /// Sets how unknown parameters are handled. Defaults to `ParseMode::Strict`.
+    pub fn set_mode(&mut self, mode: ParseMode) {
+        self.mode = mode;
+    }
+
+    /// Sets whether warnings recorded while parsing, such as the use of a deprecated parameter, are printed to stderr.
+    /// Disabled by default.
+    pub fn set_print_warnings(&mut self, enabled: bool) {
+        self.print_warnings = enabled;
+    }
+
+    /// Sets whether required parameters that are still missing after parsing are asked for on stdin. The prompt shows
+    /// the parameter's description, and the response is parsed like a command line value. Nothing is asked when stdin
+    /// is not a terminal, so `validate` reports the missing parameters as usual. A secret parameter is only asked for
+    /// if terminal echo can be turned off; otherwise it stays missing and a warning is added. Disabled by default.
+    pub fn set_prompt_on_missing(&mut self, enabled: bool) {
+        self.prompt_on_missing = enabled;
+    }
+
+    /// Sets whether `parse_command_line` prints each unknown parameter. Disabled by default.
+    pub fn set_print_unknown_parameters(&mut self, enabled: bool) {
+        self.print_unknown_parameters = enabled;
+    }
+
+    /// Returns the arguments that did not match any registered parameter.
+    pub fn unknown_parameters(&self) -> &[String] {
+        &self.unknown_parameters
+    }
+
+    /// Returns the registered alias closest to the argument, for suggesting corrections to unknown parameters.
+    /// Returns `None` if no alias is close enough to be a likely typo.
+    pub fn closest_alias(&self, argument: &str) -> Option<&str> {
+        let name = match argument.find('=') {
+            Some(index) if argument.starts_with('-') => &argument[..index],
+            _ => argument,
+        };
+
+        self.visible_parameters().into_iter()
+            .flat_map(|parameter| parameter.aliases.iter())
+            .map(|alias| alias.as_str())
+            .map(|alias| (edit_distance(name, alias), alias))
+            .filter(|&(distance, alias)| distance > 0 && distance * 3 <= alias.chars().count())
+            .min_by_key(|&(distance, _)| distance)
+            .map(|(_, alias)| alias)
+    }
+
+    /// Returns each argument acted on by the last parse, in order, paired with the name of the parameter, positional
+    /// argument or subcommand it was matched to.
+    /// 
+    /// The value of a parameter is paired with the parameter's name, a combined short flag argument has an entry for
+    /// each parameter it sets, the help and version aliases are paired with `help` and `version`, and unknown
+    /// parameters are paired with an empty name. Arguments after a standalone `--` are not included.
+    pub fn parse_trace(&self) -> &[(String, String)] {
+        &self.trace
+    }
+
+    /// Returns the warnings recorded by the `CommandLineProcessor`, such as unknown keys in a config file or aliases
+    /// that don't start with `-`.
+    /// 
+    /// Warnings recorded while parsing, such as the use of a deprecated parameter, are listed after the others and
+    /// are replaced on each parse.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// Returns the arguments that followed a standalone `--`.
+    pub fn get_trailing_args(&self) -> &[String] {
+        &self.trailing_args
+    }
+
+    /// Returns the count of a `ParameterType::Counter` parameter. Returns 0 if the parameter is unset or not a counter.
+    pub fn get_count<K: AsRef<str> + ?Sized>(&self, parameter_name: &K) -> u32 {
+        match self.get_parameter_value(parameter_name) {
+            ParameterValue::Counter(count) => *count,
+            _ => 0,
+        }
+    }
+
+    /// Returns the value of a `ParameterType::Bool` parameter. Returns `None` if the parameter is unset or not a boolean.
+    pub fn get_bool<K: AsRef<str> + ?Sized>(&self, parameter_name: &K) -> Option<bool> {
+        match self.get_parameter_value(parameter_name) {
+            ParameterValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the values of a `ParameterType::StringList` parameter. Returns an empty slice if the parameter is unset or not a string list.
+    pub fn get_string_list<K: AsRef<str> + ?Sized>(&self, parameter_name: &K) -> &[String] {
+        match self.get_parameter_value(parameter_name) {
+            ParameterValue::StringList(values) => values,
+            _ => &[],
+        }
+    }
+
+    /// Returns the value of a `ParameterType::Integer` parameter, or a `MissingParam` error if the parameter is unset
+    /// or not an integer.
+    pub fn require_integer<K: AsRef<str> + ?Sized>(&self, parameter_name: &K) -> Result<i64, MissingParam> {
+        self.get_integer(parameter_name).ok_or_else(|| missing_param(parameter_name, ParameterType::Integer))
+    }
+
+    /// Returns the value of a `ParameterType::UInteger` parameter, or a `MissingParam` error if the parameter is unset
+    /// or not an unsigned integer.
+    pub fn require_uinteger<K: AsRef<str> + ?Sized>(&self, parameter_name: &K) -> Result<u32, MissingParam> {
+        self.get_uinteger(parameter_name).ok_or_else(|| missing_param(parameter_name, ParameterType::UInteger))
+    }
+
+    /// Returns the value of a `ParameterType::ULong` parameter, or a `MissingParam` error if the parameter is unset or
+    /// not a u64 value.
+    pub fn require_ulong<K: AsRef<str> + ?Sized>(&self, parameter_name: &K) -> Result<u64, MissingParam> {
+        self.get_ulong(parameter_name).ok_or_else(|| missing_param(parameter_name, ParameterType::ULong))
+    }
+
+    /// Returns the value of a `ParameterType::Path` parameter, or a `MissingParam` error if the parameter is unset or
+    /// not a path.
+    pub fn require_path<K: AsRef<str> + ?Sized>(&self, parameter_name: &K) -> Result<&Path, MissingParam> {
+        self.get_path(parameter_name).ok_or_else(|| missing_param(parameter_name, ParameterType::Path))
+    }
+
+    /// Returns the value of a `ParameterType::String` parameter, or a `MissingParam` error if the parameter is unset or
+    /// not a string.
+    pub fn require_string<K: AsRef<str> + ?Sized>(&self, parameter_name: &K) -> Result<&str, MissingParam> {
+        self.get_string(parameter_name).ok_or_else(|| missing_param(parameter_name, ParameterType::String))
+    }
+
+    /// Returns the value of a `ParameterType::Float` parameter, or a `MissingParam` error if the parameter is unset or
+    /// not a float.
+    pub fn require_float<K: AsRef<str> + ?Sized>(&self, parameter_name: &K) -> Result<f64, MissingParam> {
+        self.get_float(parameter_name).ok_or_else(|| missing_param(parameter_name, ParameterType::Float))
+    }
+
+    /// Returns the value of a `ParameterType::Bool` parameter, or a `MissingParam` error if the parameter is unset or
+    /// not a boolean.
+    pub fn require_bool<K: AsRef<str> + ?Sized>(&self, parameter_name: &K) -> Result<bool, MissingParam> {
+        self.get_bool(parameter_name).ok_or_else(|| missing_param(parameter_name, ParameterType::Bool))
+    }
+
+    /// Returns the count of a `ParameterType::Counter` parameter, or a `MissingParam` error if the parameter is unset or
+    /// not a counter.
+    pub fn require_count<K: AsRef<str> + ?Sized>(&self, parameter_name: &K) -> Result<u32, MissingParam> {
+        match self.get_parameter_value(parameter_name) {
+            ParameterValue::Counter(count) => Ok(*count),
+            _ => Err(missing_param(parameter_name, ParameterType::Counter)),
+        }
+    }
+
+    /// Returns the paths of a `ParameterType::PathList` parameter, or a `MissingParam` error if the parameter is unset
+    /// or not a path list.
+    pub fn require_path_list<K: AsRef<str> + ?Sized>(&self, parameter_name: &K) -> Result<&[PathBuf], MissingParam> {
+        match self.get_parameter_value(parameter_name) {
+            ParameterValue::PathList(paths) => Ok(paths),
+            _ => Err(missing_param(parameter_name, ParameterType::PathList)),
+        }
+    }
+
+    /// Returns the values of a `ParameterType::StringList` parameter, or a `MissingParam` error if the parameter is
+    /// unset or not a string list.
+    pub fn require_string_list<K: AsRef<str> + ?Sized>(&self, parameter_name: &K) -> Result<&[String], MissingParam> {
+        match self.get_parameter_value(parameter_name) {
+            ParameterValue::StringList(values) => Ok(values),
+            _ => Err(missing_param(parameter_name, ParameterType::StringList)),
+        }
+    }
+
+    /// Returns true if the `CommandLineProcessor` reads `--help` or `--version` in the parameter list.
+    pub fn abort_flag(&self) -> bool {
+        self.abort_flag
+    }
+
+    /// Returns true if the last parse was given no arguments at all.
+    pub fn no_args_provided(&self) -> bool {
+        self.no_args
+    }
+
+    /// Clears the values set by parsing so the `CommandLineProcessor` can parse another set of arguments.
+    /// 
+    /// Parameters report their default values again, and the abort flag, unknown parameters, trailing arguments,
+    /// selected subcommand and warnings recorded while parsing are cleared. Registered parameters, values loaded from
+    /// a config file, warnings about them and the help and version text are kept.
+    pub fn reset_values(&mut self) {
+        for parameter in self.parameters.values_mut().chain(self.positionals.iter_mut()) {
+            parameter.value = ParameterValue::None;
+            parameter.config_value = ParameterValue::None;
+            parameter.was_set = false;
+            parameter.prompted = false;
+        }
+
+        for (_, subcommand) in self.subcommands.iter_mut() {
+            subcommand.reset_values();
+        }
+
+        self.selected_subcommand = None;
+        self.trailing_args.clear();
+        self.unknown_parameters.clear();
+        self.trace.clear();
+        self.warnings.truncate(self.setup_warnings);
+        self.no_args = false;
+        self.info_printed = false;
+        self.parse_failed = false;
+        self.abort_flag = false;
+    }
+
+    /// Returns the conventional exit code for the result of parsing.
+    /// 
+    /// Returns `Some(2)` if parsing failed, `Some(0)` if the help or version text was printed,
+    /// and `None` if the program should continue.
+    pub fn suggested_exit_code(&self) -> Option<i32> {
+        if self.parse_failed {
+            Some(2)
+        } else if self.info_printed {
+            Some(0)
+        } else {
+            None
+        }
+    }
+}
+
+/// Built-in aliases that give way to a parameter registered with the same alias.
+const SHORT_BUILTIN_ALIASES: [&str; 2] = ["-h", "-V"];
+
+/// Converts a `ParameterValue` into a JSON value.
+#[cfg(feature = "serde")]
+fn json_value(value: &ParameterValue) -> serde_json::Value {
+    match value {
+        ParameterValue::None => serde_json::Value::Null,
+        ParameterValue::Flag => serde_json::Value::Bool(true),
+        ParameterValue::UInteger(value) => serde_json::Value::from(*value),
+        ParameterValue::Path(path) => serde_json::Value::from(path.to_string_lossy().into_owned()),
+        ParameterValue::String(value) => serde_json::Value::from(value.clone()),
+        ParameterValue::Integer(value) => serde_json::Value::from(*value),
+        ParameterValue::Float(value) => serde_json::Value::from(*value),
+        ParameterValue::PathList(paths) => paths.iter().map(|path| serde_json::Value::from(path.to_string_lossy().into_owned())).collect(),
+        ParameterValue::StringList(values) => values.iter().map(|value| serde_json::Value::from(value.clone())).collect(),
+        ParameterValue::Bool(value) => serde_json::Value::Bool(*value),
+        ParameterValue::Counter(count) => serde_json::Value::from(*count),
+        ParameterValue::ULong(value) => serde_json::Value::from(*value),
+    }
+}
+
+/// Returns the text of a TOML value to parse as the parameter's value, or `None` if the TOML type doesn't fit the
+/// parameter's type. A parameter with several types accepts any number, boolean or string.
+#[cfg(feature = "toml")]
+fn toml_text(parameter: &Parameter, value: &toml::Value) -> Option<String> {
+    let any = !parameter.union_types.is_empty();
+    match value {
+        toml::Value::Boolean(value) if any || matches!(parameter.parameter_type, ParameterType::Flag | ParameterType::Bool) => Some(value.to_string()),
+        toml::Value::Integer(value) if any || matches!(parameter.parameter_type,
+            ParameterType::UInteger | ParameterType::ULong | ParameterType::Integer | ParameterType::Float | ParameterType::Counter) => Some(value.to_string()),
+        toml::Value::Float(value) if any || parameter.parameter_type == ParameterType::Float => Some(value.to_string()),
+        toml::Value::String(value) if any || matches!(parameter.parameter_type,
+            ParameterType::String | ParameterType::Path | ParameterType::PathList | ParameterType::StringList
+            | ParameterType::ExistingPath | ParameterType::ExistingDir) => Some(value.clone()),
+        _ => None,
+    }
+}
+
+/// Returns an `_arguments` spec for a zsh completion script. Returns an empty string if there are no aliases.
+fn zsh_spec(aliases: &[String], repeat: &str, description: Option<&str>, action: &str) -> String {
+    let description = match description {
+        Some(description) => format!("[{}]", zsh_escape(description)),
+        None => String::new(),
+    };
+
+    match aliases.len() {
+        0 => String::new(),
+        1 => format!("'{}{}{}{}'", repeat, aliases[0], description, action),
+        _ if !repeat.is_empty() => format!("'{}'{{{}}}'{}{}'", repeat, aliases.join(","), description, action),
+        _ => format!("'({})'{{{}}}'{}{}'", aliases.join(" "), aliases.join(","), description, action),
+    }
+}
+
+/// Escapes text for use in a quoted `_arguments` spec.
+fn zsh_escape(text: &str) -> String {
+    text.replace('\'', "'\\''")
+        .replace('[', "\\[")
+        .replace(']', "\\]")
+        .replace(':', "\\:")
+}
+
+/// Returns the Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + if a_char == *b_char { 0 } else { 1 };
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+/// Replaces each `@path` argument before a standalone `--` with the arguments read from the file.
+fn expand_response_files(args: Vec<OsString>, depth: usize) -> Result<Vec<OsString>, ParseError> {
+    let mut expanded = Vec::new();
+    let mut iter = args.into_iter();
+
+    while let Some(argument) = iter.next() {
+        if argument == "--" {
+            expanded.push(argument);
+            expanded.extend(iter.by_ref());
+            break;
+        }
+
+        let path = match argument.to_str().and_then(|argument| argument.strip_prefix('@')) {
+            Some(path) if !path.is_empty() => PathBuf::from(path),
+            _ => {
+                expanded.push(argument);
+                continue;
+            },
+        };
+
+        if depth >= 10 {
+            return Err(ParseError::new(ParseErrorKind::ResponseFileTooDeep { path }, None));
+        }
+
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err) => return Err(ParseError::new(ParseErrorKind::ResponseFileFailed { path, source: err }, None)),
+        };
+        let file_args = split_response_file(&contents).into_iter().map(OsString::from).collect();
+        expanded.extend(expand_response_files(file_args, depth + 1)?);
+    }
+
+    Ok(expanded)
+}
+
+/// Splits the contents of a response file into arguments at whitespace outside of single or double quotes.
+fn split_response_file(contents: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_argument = false;
+    let mut quote = None;
+
+    for c in contents.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => current.push(c),
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                in_argument = true;
+            },
+            None if c.is_whitespace() => {
+                if in_argument {
+                    args.push(current);
+                    current = String::new();
+                    in_argument = false;
+                }
+            },
+            None => {
+                current.push(c);
+                in_argument = true;
+            },
+        }
+    }
+
+    if in_argument {
+        args.push(current);
+    }
+    args
+}
+
+/// Splits a command line into arguments at whitespace, removing quotes and backslash escapes. See `parse_str`.
+fn split_command_line(line: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_argument = false;
+    let mut quote = None;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some('"') if c == '\\' && matches!(chars.peek(), Some('"') | Some('\\')) => current.extend(chars.next()),
+            Some(_) => current.push(c),
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                in_argument = true;
+            },
+            None if c == '\\' => {
+                current.push(chars.next().unwrap_or('\\'));
+                in_argument = true;
+            },
+            None if c.is_whitespace() => {
+                if in_argument {
+                    args.push(current);
+                    current = String::new();
+                    in_argument = false;
+                }
+            },
+            None => {
+                current.push(c);
+                in_argument = true;
+            },
+        }
+    }
+
+    if in_argument {
+        args.push(current);
+    }
+    args
+}
+
+/// Splits text into lines of at most `width` characters, breaking at whitespace. Words longer than `width` are kept whole.
+fn wrap_text(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+            lines.push(line);
+            line = String::new();
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Returns true if the value can be stored in a parameter of the given type. `ParameterValue::None` matches every type.
+fn value_matches_type(value: &ParameterValue, parameter_type: &ParameterType) -> bool {
+    matches!((value, parameter_type),
+        (ParameterValue::None, _) |
+        (ParameterValue::Flag, ParameterType::Flag) |
+        (ParameterValue::Bool(_), ParameterType::Flag) |
+        (ParameterValue::UInteger(_), ParameterType::UInteger) |
+        (ParameterValue::Path(_), ParameterType::Path) |
+        (ParameterValue::Path(_), ParameterType::ExistingPath) |
+        (ParameterValue::Path(_), ParameterType::ExistingDir) |
+        (ParameterValue::String(_), ParameterType::String) |
+        (ParameterValue::Integer(_), ParameterType::Integer) |
+        (ParameterValue::Float(_), ParameterType::Float) |
+        (ParameterValue::PathList(_), ParameterType::PathList) |
+        (ParameterValue::StringList(_), ParameterType::StringList) |
+        (ParameterValue::Bool(_), ParameterType::Bool) |
+        (ParameterValue::Counter(_), ParameterType::Counter) |
+        (ParameterValue::ULong(_), ParameterType::ULong))
+}
+
+impl Default for CommandLineProcessor {
+    fn default() -> CommandLineProcessor {
+        CommandLineProcessor::new()
+    }
+}
+
+/// Creates a `CommandLineProcessor` with the listed parameters, each given as `name: Type => [aliases]`.
+/// 
+/// ```
+/// #[macro_use]
+/// extern crate cmdpro;
+/// 
+/// # fn main() {
+/// let mut processor = parameters! {
+///     verbose: Flag => ["--verbose", "-v"],
+///     count: UInteger => ["--count"],
+/// };
+/// 
+/// processor.try_parse_args(vec!["-v".to_owned(), "--count".to_owned(), "3".to_owned()]).unwrap();
+/// assert!(processor.is_flag_set("verbose"));
+/// assert_eq!(processor.get_uinteger("count"), Some(3));
+/// # }
+/// ```
+/// 
+/// # Panics
+/// Panics if a parameter name or alias is used more than once.
+#[macro_export]
+macro_rules! parameters {
+    ($($name:ident : $parameter_type:ident => [$($alias:expr),* $(,)?]),* $(,)?) => {{
+        let mut processor = $crate::CommandLineProcessor::new();
+        $(
+            let aliases: Vec<&str> = vec![$($alias),*];
+            processor.add_parameter(stringify!($name), $crate::ParameterType::$parameter_type, aliases);
+        )*
+        processor
+    }};
+}
+
+/// Builder for configuring a `CommandLineProcessor` fluently.
+pub struct CommandLineProcessorBuilder {
+    processor: CommandLineProcessor,
+}
+
+impl CommandLineProcessorBuilder {
+    /// Returns a new `CommandLineProcessorBuilder`.
+    pub fn new() -> CommandLineProcessorBuilder {
+        CommandLineProcessorBuilder {
+            processor: CommandLineProcessor::new(),
+        }
+    }
+
+    /// Add a parameter to be parsed. See `CommandLineProcessor::add_parameter`.
+    pub fn parameter<A: IntoIterator>(mut self, parameter_name: &str, parameter_type: ParameterType, aliases: A) -> CommandLineProcessorBuilder where A::Item: AsRef<str> {
+        self.processor.add_parameter(parameter_name, parameter_type, aliases);
+        self
+    }
+
+    /// Add a parameter with a default value. See `CommandLineProcessor::add_parameter_with_default`.
+    pub fn parameter_with_default<A: IntoIterator>(mut self, parameter_name: &str, parameter_type: ParameterType, aliases: A, default: ParameterValue) -> CommandLineProcessorBuilder where A::Item: AsRef<str> {
+        self.processor.add_parameter_with_default(parameter_name, parameter_type, aliases, default);
+        self
+    }
+
+    /// Add a positional argument. See `CommandLineProcessor::add_positional`.
+    pub fn positional(mut self, parameter_name: &str, parameter_type: ParameterType) -> CommandLineProcessorBuilder {
+        self.processor.add_positional(parameter_name, parameter_type);
+        self
+    }
+
+    /// Sets the description of a parameter. See `CommandLineProcessor::set_description`.
+    pub fn description(mut self, parameter_name: &str, description: &str) -> CommandLineProcessorBuilder {
+        self.processor.set_description(parameter_name, description);
+        self
+    }
+
+    /// Marks a parameter as required. See `CommandLineProcessor::set_required`.
+    pub fn required(mut self, parameter_name: &str) -> CommandLineProcessorBuilder {
+        self.processor.set_required(parameter_name);
+        self
+    }
+
+    /// Sets the text to print when the `--help` parameter is used.
+    pub fn help_text(mut self, help_text: &str) -> CommandLineProcessorBuilder {
+        self.processor.set_help_text(help_text);
+        self
+    }
+
+    /// Sets the text to print when the `--version` parameter is used.
+    pub fn version_text(mut self, version_text: &str) -> CommandLineProcessorBuilder {
+        self.processor.set_version_text(version_text);
+        self
+    }
+
+    /// Sets the program name shown in the usage line of the generated help text.
+    pub fn program_name(mut self, name: &str) -> CommandLineProcessorBuilder {
+        self.processor.set_program_name(name);
+        self
+    }
+
+    /// Sets a one-line description of the program shown in the generated help text.
+    pub fn about(mut self, about: &str) -> CommandLineProcessorBuilder {
+        self.processor.set_about(about);
+        self
+    }
+
+    /// Prints the help text when the program is run without arguments. See `CommandLineProcessor::set_print_help_if_no_args`.
+    pub fn print_help_if_no_args(mut self, enabled: bool) -> CommandLineProcessorBuilder {
+        self.processor.set_print_help_if_no_args(enabled);
+        self
+    }
+
+    /// Returns the configured `CommandLineProcessor`.
+    pub fn build(self) -> CommandLineProcessor {
+        self.processor
+    }
+}
+
+impl Default for CommandLineProcessorBuilder {
+    fn default() -> CommandLineProcessorBuilder {
+        CommandLineProcessorBuilder::new()
+    }
+}
+
+/// Returns the value placeholder shown in help text for the parameter type.
+fn type_placeholder(parameter_type: &ParameterType) -> Option<&'static str> {
+    match parameter_type {
+        ParameterType::Flag => None,
+        ParameterType::UInteger => Some("<uint>"),
+        ParameterType::Path => Some("<path>"),
+        ParameterType::String => Some("<string>"),
+        ParameterType::Integer => Some("<int>"),
+        ParameterType::Float => Some("<float>"),
+        ParameterType::PathList => Some("<path>"),
+        ParameterType::StringList => Some("<string>"),
+        ParameterType::ExistingPath => Some("<file>"),
+        ParameterType::ExistingDir => Some("<dir>"),
+        ParameterType::Bool => Some("<bool>"),
+        ParameterType::Counter => None,
+        ParameterType::ULong => Some("<ulong>"),
+    }
+}
+
+/// Converts an integer default to the numeric type of the parameter. Other values, and integers that don't fit the
+/// type, are returned unchanged.
+fn integer_default(default: ParameterValue, parameter_type: &ParameterType) -> ParameterValue {
+    let value = match default {
+        ParameterValue::Integer(value) => value,
+        default => return default,
+    };
+
+    match parameter_type {
+        ParameterType::UInteger => u32::try_from(value).map(ParameterValue::UInteger).unwrap_or(default),
+        ParameterType::Counter => u32::try_from(value).map(ParameterValue::Counter).unwrap_or(default),
+        ParameterType::ULong => u64::try_from(value).map(ParameterValue::ULong).unwrap_or(default),
+        ParameterType::Float => ParameterValue::Float(value as f64),
+        _ => default,
+    }
+}
+
+/// Stores a value in place of the current value. List values are appended to the current list instead.
+fn merge_value(current: &mut ParameterValue, value: ParameterValue) {
+    match (current, value) {
+        (ParameterValue::PathList(paths), ParameterValue::PathList(mut new_paths)) => paths.append(&mut new_paths),
+        (ParameterValue::StringList(values), ParameterValue::StringList(mut new_values)) => values.append(&mut new_values),
+        (current, value) => *current = value,
+    }
+}
+
+/// Splits a list value on the parameter's delimiter. Returns the whole value if the parameter has no delimiter.
+fn split_value(parameter: &Parameter, value: String) -> Vec<String> {
+    match parameter.delimiter {
+        Some(delimiter) => value.split(delimiter)
+            .filter(|value| parameter.keep_empty_values || !value.is_empty())
+            .map(|value| value.to_owned())
+            .collect(),
+        None => vec![value],
+    }
+}
+
+/// Returns true if the argument is one of the words accepted by a `ParameterType::Bool` parameter, such as `true` or `no`.
+fn is_bool_literal(argument: &str) -> bool {
+    matches!(argument.to_lowercase().as_ref(), "true" | "false" | "yes" | "no" | "1" | "0")
+}
+
+/// Converts a config file value into the `ParameterValue` for the parameter's type. Flags are read as booleans.
+fn parse_config_value(parameter: &Parameter, value: &str) -> Result<ParameterValue, ParseError> {
+    if let ParameterType::Flag = parameter.parameter_type {
+        return match value.to_lowercase().as_ref() {
+            "true" | "yes" | "1" => Ok(parameter.flag_value()),
+            "false" | "no" | "0" if parameter.negatable => Ok(ParameterValue::Bool(false)),
+            "false" | "no" | "0" => Ok(ParameterValue::None),
+            _ => Err(ParseError::new(ParseErrorKind::InvalidBool {
+                parameter: parameter.parameter_name.clone(),
+                value: value.to_owned(),
+            }, None)),
+        };
+    }
+
+    parse_value(parameter, value.to_owned())
+}
+
+/// Replaces the value of a secret parameter in traces and serialized output.
+const REDACTED: &str = "***";
+
+/// Builds the error returned by the `require_*` getters.
+fn missing_param<K: AsRef<str> + ?Sized>(parameter_name: &K, expected: ParameterType) -> MissingParam {
+    MissingParam { parameter: parameter_name.as_ref().to_string(), expected }
+}
+
+/// Returns a value as text for `describe_state`. Lists are separated by commas.
+fn value_text(value: &ParameterValue) -> String {
+    match value {
+        ParameterValue::None => String::new(),
+        ParameterValue::Flag => "true".to_owned(),
+        ParameterValue::UInteger(value) => value.to_string(),
+        ParameterValue::Path(path) => path.display().to_string(),
+        ParameterValue::String(value) => value.clone(),
+        ParameterValue::Integer(value) => value.to_string(),
+        ParameterValue::Float(value) => value.to_string(),
+        ParameterValue::PathList(paths) => paths.iter().map(|path| path.display().to_string()).collect::<Vec<String>>().join(", "),
+        ParameterValue::StringList(values) => values.join(", "),
+        ParameterValue::Bool(value) => value.to_string(),
+        ParameterValue::Counter(count) => count.to_string(),
+        ParameterValue::ULong(value) => value.to_string(),
+    }
+}
+
+/// Returns the text of an argument for the parse trace, or `***` if the parameter is secret.
+fn shown(parameter: &Parameter, text: &str) -> String {
+    match parameter.secret {
+        true => REDACTED.to_owned(),
+        false => text.to_owned(),
+    }
+}
+
+/// Returns the parameter's value for trace logs, or `***` if the parameter is secret.
+#[cfg(feature = "log")]
+fn shown_value(parameter: &Parameter) -> String {
+    shown(parameter, &format!("{:?}", parameter.value))
+}
+
+/// Turns terminal echo on or off while a secret parameter is asked for. Returns false if `stty` is missing or fails.
+#[cfg(unix)]
+fn set_terminal_echo(enabled: bool) -> bool {
+    let setting = if enabled { "echo" } else { "-echo" };
+    match std::process::Command::new("stty").arg(setting).status() {
+        Ok(status) => status.success(),
+        Err(_) => false,
+    }
+}
+
+/// Echo can't be turned off on this platform, so secret parameters are never asked for.
+#[cfg(not(unix))]
+fn set_terminal_echo(_enabled: bool) -> bool {
+    false
+}
+
+/// Converts a command line value into the `ParameterValue` for the parameter's type and runs the parameter's validator.
+fn parse_value(parameter: &Parameter, value: String) -> Result<ParameterValue, ParseError> {
+    let value = convert_value(parameter, value)?;
+    validate_value(parameter, value)
+}
+
+/// Converts a command line argument that may not be valid UTF-8. Path values are taken from the argument unchanged,
+/// so a `ParameterType::PathList` value that is not valid UTF-8 is not split on the delimiter.
+fn parse_os_value(parameter: &Parameter, value: OsString) -> Result<ParameterValue, ParseError> {
+    let value = match value.into_string() {
+        Ok(value) => return parse_value(parameter, value),
+        Err(value) => value,
+    };
+
+    let value = match parameter.parameter_type {
+        ParameterType::Path => ParameterValue::Path(PathBuf::from(value)),
+        ParameterType::PathList => ParameterValue::PathList(vec![PathBuf::from(value)]),
+        ParameterType::ExistingPath | ParameterType::ExistingDir => check_path(parameter, PathBuf::from(value))?,
+        _ => return parse_value(parameter, value.to_string_lossy().into_owned()),
+    };
+    validate_value(parameter, value)
+}
+
+/// Checks that the path for a `ParameterType::ExistingPath` or `ParameterType::ExistingDir` parameter exists.
+fn check_path(parameter: &Parameter, path: PathBuf) -> Result<ParameterValue, ParseError> {
+    match parameter.parameter_type {
+        ParameterType::ExistingPath => {
+            if parameter.allow_stdin && path.as_os_str() == "-" {
+                Ok(ParameterValue::Path(path))
+            } else if !path.exists() {
+                Err(ParseError::new(ParseErrorKind::PathNotFound { parameter: parameter.parameter_name.clone(), path }, None))
+            } else if !path.is_file() {
+                Err(ParseError::new(ParseErrorKind::NotAFile { parameter: parameter.parameter_name.clone(), path }, None))
+            } else {
+                Ok(ParameterValue::Path(path))
+            }
+        },
+        _ => {
+            if !path.exists() {
+                Err(ParseError::new(ParseErrorKind::PathNotFound { parameter: parameter.parameter_name.clone(), path }, None))
+            } else if !path.is_dir() {
+                Err(ParseError::new(ParseErrorKind::NotADirectory { parameter: parameter.parameter_name.clone(), path }, None))
+            } else {
+                Ok(ParameterValue::Path(path))
+            }
+        },
+    }
+}
+
+/// Converts the value of a parameter with several types to the first type that accepts it, then to one of its keywords.
+fn convert_union_value(parameter: &Parameter, value: String) -> Result<ParameterValue, ParseError> {
+    for parameter_type in &parameter.union_types {
+        let mut candidate = parameter.clone();
+        candidate.parameter_type = parameter_type.clone();
+        candidate.union_types.clear();
+
+        if let Ok(converted) = convert_value(&candidate, value.clone()) {
+            return Ok(converted);
+        }
+    }
+
+    if parameter.keywords.contains(&value) {
+        return Ok(ParameterValue::String(value));
+    }
+
+    Err(ParseError::new(ParseErrorKind::NoMatchingType {
+        parameter: parameter.parameter_name.clone(),
+        value,
+    }, None))
+}
+
+/// Expands `~`, `$VAR` and `${VAR}` in a path value if the parameter has path expansion enabled.
+/// A `$` that is not followed by a variable name is kept.
+fn expand_path(parameter: &Parameter, value: String) -> Result<String, ParseError> {
+    let policy = match parameter.expand_path {
+        Some(policy) => policy,
+        None => return Ok(value),
+    };
+
+    let lookup = |variable: &str| match env::var(variable) {
+        Ok(value) => Ok(value),
+        Err(_) if policy == UnsetVariablePolicy::Empty => Ok(String::new()),
+        Err(_) => Err(ParseError::new(ParseErrorKind::UnsetVariable {
+            parameter: parameter.parameter_name.clone(),
+            variable: variable.to_owned(),
+        }, None)),
+    };
+
+    let mut expanded = String::new();
+    let mut rest: &str = &value;
+    if rest == "~" || rest.starts_with("~/") {
+        expanded.push_str(&lookup("HOME")?);
+        rest = &rest[1..];
+    }
+
+    while let Some(start) = rest.find('$') {
+        expanded.push_str(&rest[..start]);
+        rest = &rest[start + 1..];
+
+        let (variable, remainder) = match rest.strip_prefix('{') {
+            Some(braced) => match braced.find('}') {
+                Some(end) => (&braced[..end], &braced[end + 1..]),
+                None => ("", rest),
+            },
+            None => {
+                let end = rest.find(|c: char| !c.is_ascii_alphanumeric() && c != '_').unwrap_or(rest.len());
+                (&rest[..end], &rest[end..])
+            },
+        };
+
+        if variable.is_empty() || variable.starts_with(|c: char| c.is_ascii_digit()) {
+            expanded.push('$');
+            continue;
+        }
+
+        expanded.push_str(&lookup(variable)?);
+        rest = remainder;
+    }
+    expanded.push_str(rest);
+
+    Ok(expanded)
+}
+
+/// Checks the parameter's absolute or relative path requirement and runs the parameter's validator on a converted value.
+fn validate_value(parameter: &Parameter, value: ParameterValue) -> Result<ParameterValue, ParseError> {
+    let paths: Vec<&PathBuf> = match &value {
+        ParameterValue::Path(path) => vec![path],
+        ParameterValue::PathList(paths) => paths.iter().collect(),
+        _ => Vec::new(),
+    };
+
+    for path in paths {
+        if parameter.allow_stdin && path.as_os_str() == "-" {
+            continue;
+        }
+
+        if parameter.require_absolute && !path.is_absolute() {
+            return Err(ParseError::new(ParseErrorKind::PathNotAbsolute { parameter: parameter.parameter_name.clone(), path: path.clone() }, None));
+        }
+
+        if parameter.require_relative && path.is_absolute() {
+            return Err(ParseError::new(ParseErrorKind::PathNotRelative { parameter: parameter.parameter_name.clone(), path: path.clone() }, None));
+        }
+    }
+
+    // A `-` kept in place of standard input by `analyze` is not what the validator would see.
+    let stdin_kept = parameter.allow_stdin && parameter.stdin_disabled && matches!(&value, ParameterValue::String(text) if text == "-");
+
+    if let Some(validator) = parameter.validator.as_ref().filter(|_| !stdin_kept) {
+        if let Err(message) = validator(&value) {
+            return Err(ParseError::new(ParseErrorKind::ValidationFailed {
+                parameter: parameter.parameter_name.clone(),
+                message,
+            }, None));
+        }
+    }
+
+    Ok(value)
+}
+
+/// Reads all of standard input as the value of a parameter, removing trailing newlines.
+fn read_stdin(parameter: &Parameter) -> Result<String, ParseError> {
+    let mut value = String::new();
+    match io::stdin().read_to_string(&mut value) {
+        Ok(_) => {
+            let length = value.trim_end_matches(['\n', '\r']).len();
+            value.truncate(length);
+            Ok(value)
+        },
+        Err(err) => Err(ParseError::new(ParseErrorKind::StdinFailed {
+            parameter: parameter.parameter_name.clone(),
+            source: err,
+        }, None)),
+    }
+}
+
+/// Parses the value of a `ParameterType::UInteger` parameter. Underscores are ignored, and a unit suffix is applied
+/// if the parameter allows one. A value above `u32::MAX` is reported as `ParseErrorKind::ValueTooLarge`.
+fn parse_uinteger(parameter: &Parameter, value: &str) -> Result<u32, ParseError> {
+    let mut digits = value.replace('_', "");
+    let mut multiplier: u64 = 1;
+
+    if parameter.allow_size_suffix {
+        let suffixes = [("ki", 1 << 10), ("mi", 1 << 20), ("gi", 1 << 30), ("k", 1_000), ("m", 1_000_000), ("g", 1_000_000_000)];
+        // The suffix is compared byte by byte, so a character such as the Kelvin sign, which lowercases to `k`, can't
+        // leave the cut in the middle of a character.
+        let has_suffix = |suffix: &str| digits.len().checked_sub(suffix.len())
+            .and_then(|start| digits.get(start..))
+            .is_some_and(|tail| tail.eq_ignore_ascii_case(suffix));
+        if let Some(&(suffix, suffix_multiplier)) = suffixes.iter().find(|(suffix, _)| has_suffix(suffix)) {
+            let length = digits.len() - suffix.len();
+            digits.truncate(length);
+            multiplier = suffix_multiplier;
+        }
+    }
+
+    let too_large = || ParseError::new(ParseErrorKind::ValueTooLarge {
+        parameter: parameter.parameter_name.clone(),
+        value: value.to_owned(),
+        max: u64::from(u32::MAX),
+    }, None);
+    let invalid = |err: ParseIntError| match err.kind() {
+        IntErrorKind::PosOverflow => too_large(),
+        _ => ParseError::new(ParseErrorKind::InvalidUInteger {
+            parameter: parameter.parameter_name.clone(),
+            source: err,
+        }, None),
+    };
+
+    if multiplier == 1 {
+        return digits.parse::<u32>().map_err(invalid);
+    }
+
+    match digits.parse::<u64>().map_err(invalid)?.checked_mul(multiplier) {
+        Some(number) if number <= u64::from(u32::MAX) => Ok(number as u32),
+        _ => Err(too_large()),
+    }
+}
+
+/// Converts a command line value into the `ParameterValue` for the parameter's type.
+fn convert_value(parameter: &Parameter, value: String) -> Result<ParameterValue, ParseError> {
+    if !parameter.union_types.is_empty() {
+        return convert_union_value(parameter, value);
+    }
+
+    match parameter.parameter_type {
+        ParameterType::Flag => Err(ParseError::new(ParseErrorKind::UnexpectedValue {
+            flag: parameter.aliases.first().unwrap_or(&parameter.parameter_name).clone(),
+        }, None)),
+        ParameterType::UInteger => {
+            let value = parse_uinteger(parameter, &value)?;
+            match parameter.range {
+                Some((min, max)) if value < min || value > max => Err(ParseError::new(ParseErrorKind::OutOfRange {
+                    parameter: parameter.parameter_name.clone(),
+                    value,
+                    min,
+                    max,
+                }, None)),
+                _ => Ok(ParameterValue::UInteger(value)),
+            }
+        },
+        ParameterType::Path => {
+            let mut path = PathBuf::new();
+            path.push(expand_path(parameter, value)?);
+            Ok(ParameterValue::Path(path))
+        },
+        ParameterType::String => {
+            // The value that would be read isn't known, so it is not checked against the choices.
+            if parameter.allow_stdin && parameter.stdin_disabled && value == "-" {
+                return Ok(ParameterValue::String(value));
+            }
+
+            let value = if parameter.allow_stdin && value == "-" {
+                read_stdin(parameter)?
+            } else {
+                value
+            };
+
+            match &parameter.choices {
+                Some(choices) => {
+                    let choice = choices.iter().find(|choice| {
+                        if parameter.choices_case_insensitive {
+                            choice.to_lowercase() == value.to_lowercase()
+                        } else {
+                            **choice == value
+                        }
+                    });
+
+                    match choice {
+                        Some(choice) => Ok(ParameterValue::String(choice.clone())),
+                        None => Err(ParseError::new(ParseErrorKind::InvalidChoice {
+                            parameter: parameter.parameter_name.clone(),
+                            value,
+                            choices: choices.clone(),
+                        }, None)),
+                    }
+                },
+                None => Ok(ParameterValue::String(value)),
+            }
+        },
+        ParameterType::Integer => {
+            match i64::from_str(&value) {
+                Ok(value) => Ok(ParameterValue::Integer(value)),
+                Err(err) => Err(ParseError::new(ParseErrorKind::InvalidInteger {
+                    parameter: parameter.parameter_name.clone(),
+                    source: err,
+                }, None)),
+            }
+        },
+        ParameterType::Float => {
+            match f64::from_str(&value) {
+                Ok(float) if float.is_finite() => Ok(ParameterValue::Float(float)),
+                _ => Err(ParseError::new(ParseErrorKind::InvalidFloat {
+                    parameter: parameter.parameter_name.clone(),
+                    value,
+                }, None)),
+            }
+        },
+        ParameterType::PathList => {
+            let paths = split_value(parameter, value).into_iter()
+                .map(|value| expand_path(parameter, value).map(PathBuf::from))
+                .collect::<Result<Vec<PathBuf>, ParseError>>()?;
+            Ok(ParameterValue::PathList(paths))
+        },
+        ParameterType::StringList => Ok(ParameterValue::StringList(split_value(parameter, value))),
+        ParameterType::ExistingPath | ParameterType::ExistingDir => check_path(parameter, PathBuf::from(expand_path(parameter, value)?)),
+        ParameterType::Bool => {
+            match value.to_lowercase().as_ref() {
+                "true" | "yes" | "1" => Ok(ParameterValue::Bool(true)),
+                "false" | "no" | "0" => Ok(ParameterValue::Bool(false)),
+                _ => Err(ParseError::new(ParseErrorKind::InvalidBool {
+                    parameter: parameter.parameter_name.clone(),
+                    value,
+                }, None)),
+            }
+        },
+        ParameterType::Counter => {
+            match value.parse::<u32>() {
+                Ok(count) => Ok(ParameterValue::Counter(count)),
+                Err(err) => Err(ParseError::new(ParseErrorKind::InvalidUInteger {
+                    parameter: parameter.parameter_name.clone(),
+                    source: err,
+                }, None)),
+            }
+        },
+        ParameterType::ULong => {
+            match u64::from_str(&value) {
+                Ok(value) => Ok(ParameterValue::ULong(value)),
+                Err(ref err) if *err.kind() == IntErrorKind::PosOverflow => Err(ParseError::new(ParseErrorKind::ValueTooLarge {
+                    parameter: parameter.parameter_name.clone(),
+                    value,
+                    max: u64::MAX,
+                }, None)),
+                Err(err) => Err(ParseError::new(ParseErrorKind::InvalidUInteger {
+                    parameter: parameter.parameter_name.clone(),
+                    source: err,
+                }, None)),
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(arguments: &[&str]) -> Vec<String> {
+        arguments.iter().map(|argument| argument.to_string()).collect()
+    }
+
+    #[test]
+    fn empty_string_value_is_stored() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("name", ParameterType::String, &["--name"]);
+        processor.try_parse_args(args(&["--name", ""])).unwrap();
+        assert_eq!(processor.get_string("name"), Some(""));
+    }
+
+    #[test]
+    fn integer_accepts_negative_zero_and_positive_values() {
+        for &(text, expected) in &[("-5", -5), ("0", 0), ("42", 42)] {
+            let mut processor = CommandLineProcessor::new();
+            processor.add_parameter("offset", ParameterType::Integer, &["--offset"]);
+            processor.try_parse_args(args(&["--offset", text])).unwrap();
+            assert_eq!(processor.get_parameter_value("offset"), &ParameterValue::Integer(expected));
+        }
+    }
+
+    #[test]
+    fn integer_rejects_non_numbers() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("offset", ParameterType::Integer, &["--offset"]);
+        let errors = processor.try_parse_args(args(&["--offset", "-5x"])).unwrap_err();
+        assert!(matches!(errors[0].kind(), ParseErrorKind::InvalidInteger { .. }));
+        assert_eq!(errors[0].index(), Some(1));
+    }
+
+    #[test]
+    fn float_accepts_exponents_and_signs() {
+        for &(text, expected) in &[("1e-3", 0.001), ("+2.5", 2.5), ("-0.5", -0.5), ("3", 3.0)] {
+            let mut processor = CommandLineProcessor::new();
+            processor.add_parameter("ratio", ParameterType::Float, &["--ratio"]);
+            processor.try_parse_args(args(&["--ratio", text])).unwrap();
+            assert_eq!(processor.get_float("ratio"), Some(expected));
+        }
+    }
+
+    #[test]
+    fn float_rejects_infinite_and_nan_values() {
+        for text in &["inf", "-inf", "infinity", "nan", "NaN", "1e400"] {
+            let mut processor = CommandLineProcessor::new();
+            processor.add_parameter("ratio", ParameterType::Float, &["--ratio"]);
+            let errors = processor.try_parse_args(args(&["--ratio", text])).unwrap_err();
+            assert!(matches!(errors[0].kind(), ParseErrorKind::InvalidFloat { .. }), "{} was accepted", text);
+            assert_eq!(processor.get_float("ratio"), None);
+        }
+    }
+
+    #[test]
+    fn inline_value_keeps_everything_after_the_first_equals_sign() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("path", ParameterType::Path, &["--path"]);
+        processor.add_parameter("name", ParameterType::String, &["--name"]);
+        processor.try_parse_args(args(&["--path=/tmp/a=b", "--name=="])).unwrap();
+        assert_eq!(processor.get_path("path"), Some(Path::new("/tmp/a=b")));
+        assert_eq!(processor.get_string("name"), Some("="));
+    }
+
+    #[test]
+    fn builtin_help_and_version_aliases_abort() {
+        for alias in &["--help", "-h", "--h", "--version", "-V", "--v"] {
+            let mut processor = CommandLineProcessor::new();
+            processor.try_parse_args(args(&[alias])).unwrap();
+            assert!(processor.abort_flag(), "{} did not abort", alias);
+        }
+    }
+
+    #[test]
+    fn registered_short_alias_takes_precedence_over_builtin() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("host", ParameterType::String, &["--host", "-h"]);
+        processor.try_parse_args(args(&["-h", "example.com"])).unwrap();
+        assert!(!processor.abort_flag());
+        assert_eq!(processor.get_string("host"), Some("example.com"));
+        assert!(processor.check_aliases().is_ok());
+
+        processor.reset_values();
+        processor.try_parse_args(args(&["--help"])).unwrap();
+        assert!(processor.abort_flag());
+    }
+
+    fn flags_processor() -> CommandLineProcessor {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("all", ParameterType::Flag, &["-a"]);
+        processor.add_parameter("brief", ParameterType::Flag, &["-b"]);
+        processor.add_parameter("color", ParameterType::Flag, &["-c"]);
+        processor
+    }
+
+    #[test]
+    fn combined_short_flags_set_every_flag() {
+        let mut processor = flags_processor();
+        processor.try_parse_args(args(&["-abc"])).unwrap();
+        assert!(processor.is_flag_set("all"));
+        assert!(processor.is_flag_set("brief"));
+        assert!(processor.is_flag_set("color"));
+    }
+
+    #[test]
+    fn combined_short_flags_report_the_unknown_character() {
+        let mut processor = flags_processor();
+        let errors = processor.try_parse_args(args(&["-abx"])).unwrap_err();
+        match errors[0].kind() {
+            ParseErrorKind::InvalidShortFlag { argument, flag } => {
+                assert_eq!(argument, "-abx");
+                assert_eq!(*flag, 'x');
+            },
+            kind => panic!("unexpected error {:?}", kind),
+        }
+        assert!(!processor.is_flag_set("all"));
+    }
+
+    #[test]
+    fn negative_number_is_a_positional_value() {
+        let mut processor = flags_processor();
+        processor.add_positional("offset", ParameterType::Integer);
+        processor.try_parse_args(args(&["-a", "-42"])).unwrap();
+        assert!(processor.is_flag_set("all"));
+        assert_eq!(processor.get_positional("offset"), &ParameterValue::Integer(-42));
+    }
+
+    #[test]
+    fn digit_short_aliases_are_combined_flags() {
+        let mut processor = flags_processor();
+        processor.add_parameter("one", ParameterType::Flag, &["-1"]);
+        processor.add_parameter("two", ParameterType::Flag, &["-2"]);
+        processor.try_parse_args(args(&["-12"])).unwrap();
+        assert!(processor.is_flag_set("one"));
+        assert!(processor.is_flag_set("two"));
+    }
+
+    #[test]
+    fn range_accepts_bounds_and_rejects_values_outside() {
+        for &(text, accepted) in &[("1", true), ("64", true), ("0", false), ("65", false)] {
+            let mut processor = CommandLineProcessor::new();
+            processor.add_parameter("threads", ParameterType::UInteger, &["--threads"]);
+            processor.set_range("threads", 1, 64);
+            let result = processor.try_parse_args(args(&["--threads", text]));
+            if accepted {
+                assert!(result.is_ok(), "{} was rejected", text);
+                assert_eq!(processor.get_uinteger("threads"), Some(text.parse().unwrap()));
+            } else {
+                match result.unwrap_err()[0].kind() {
+                    ParseErrorKind::OutOfRange { value, min, max, .. } => assert_eq!((*value, *min, *max), (text.parse().unwrap(), 1, 64)),
+                    kind => panic!("unexpected error {:?}", kind),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn env_fallback_parses_flags_like_config_values() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("verbose", ParameterType::Flag, &["--verbose"]);
+        processor.add_parameter("color", ParameterType::Flag, &["--color"]);
+        processor.set_negatable("color");
+        processor.add_parameter("port", ParameterType::UInteger, &["--port"]);
+        processor.set_env_fallback("verbose", "CMDPRO_TEST_ENV_VERBOSE");
+        processor.set_env_fallback("color", "CMDPRO_TEST_ENV_COLOR");
+        processor.set_env_fallback("port", "CMDPRO_TEST_ENV_PORT");
+
+        env::set_var("CMDPRO_TEST_ENV_VERBOSE", "0");
+        env::set_var("CMDPRO_TEST_ENV_COLOR", "false");
+        env::set_var("CMDPRO_TEST_ENV_PORT", "8080");
+        processor.try_parse_args(args(&["--port", "9000"])).unwrap();
+        assert!(!processor.is_flag_set("verbose"));
+        assert_eq!(processor.get_parameter_value("color"), &ParameterValue::Bool(false));
+        assert_eq!(processor.get_uinteger("port"), Some(9000));
+
+        processor.reset_values();
+        env::set_var("CMDPRO_TEST_ENV_VERBOSE", "yes");
+        env::set_var("CMDPRO_TEST_ENV_PORT", "many");
+        let errors = processor.try_parse_args(args(&[])).unwrap_err();
+        assert!(processor.is_flag_set("verbose"));
+        match errors[0].kind() {
+            ParseErrorKind::InvalidEnvValue { variable, .. } => assert_eq!(variable, "CMDPRO_TEST_ENV_PORT"),
+            kind => panic!("unexpected error {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn closest_alias_suggests_close_typos_only() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("verbose", ParameterType::Flag, &["--verbose"]);
+        processor.add_parameter("output", ParameterType::Path, &["--output"]);
+        assert_eq!(processor.closest_alias("--verbsoe"), Some("--verbose"));
+        assert_eq!(processor.closest_alias("--outptu=x"), Some("--output"));
+        assert_eq!(processor.closest_alias("--frobnicate"), None);
+
+        let errors = processor.try_parse_args(args(&["--verbsoe"])).unwrap_err();
+        assert!(matches!(errors[0].kind(), ParseErrorKind::UnknownParameter { argument, .. } if argument == "--verbsoe"));
+        assert_eq!(processor.unknown_parameters(), &["--verbsoe".to_owned()]);
+    }
+
+    #[test]
+    fn negatable_flag_distinguishes_on_off_and_unset() {
+        for &(arguments, ref expected) in &[
+            (&["--color"][..], ParameterValue::Bool(true)),
+            (&["--no-color"][..], ParameterValue::Bool(false)),
+            (&[][..], ParameterValue::None),
+        ] {
+            let mut processor = CommandLineProcessor::new();
+            processor.add_parameter("color", ParameterType::Flag, &["--color"]);
+            processor.set_negatable("color");
+            processor.try_parse_args(args(arguments)).unwrap();
+            assert_eq!(processor.get_parameter_value("color"), expected);
+            assert_eq!(processor.is_flag_set("color"), *expected == ParameterValue::Bool(true));
+        }
+    }
+
+    #[test]
+    fn counter_counts_each_occurrence() {
+        for &(arguments, expected) in &[(&[][..], 0), (&["-v"][..], 1), (&["-v", "--verbose", "-vv"][..], 4), (&["-vvv"][..], 3)] {
+            let mut processor = CommandLineProcessor::new();
+            processor.add_parameter("verbose", ParameterType::Counter, &["--verbose", "-v"]);
+            processor.try_parse_args(args(arguments)).unwrap();
+            assert_eq!(processor.get_count("verbose"), expected, "{:?}", arguments);
+        }
+    }
+
+    #[test]
+    fn permissive_mode_collects_unknown_parameters() {
+        let mut processor = flags_processor();
+        processor.set_mode(ParseMode::Permissive);
+        processor.try_parse_args(args(&["--foo", "-Wall", "-ab"])).unwrap();
+        assert!(!processor.abort_flag());
+        assert_eq!(processor.unknown_parameters(), &["--foo".to_owned(), "-Wall".to_owned()]);
+        assert!(processor.is_flag_set("all"));
+        assert!(processor.is_flag_set("brief"));
+    }
+
+    #[test]
+    fn strict_mode_rejects_unknown_parameters() {
+        let mut processor = flags_processor();
+        let errors = processor.try_parse_args(args(&["--foo", "-Wall"])).unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert!(processor.abort_flag());
+    }
+
+    #[test]
+    fn case_insensitive_aliases_keep_the_value_case() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("verbose", ParameterType::Flag, &["--verbose"]);
+        processor.add_parameter("name", ParameterType::String, &["--name"]);
+        processor.set_case_insensitive(true);
+        processor.try_parse_args(args(&["--VERBOSE", "--Name=ValUE"])).unwrap();
+        assert!(processor.is_flag_set("verbose"));
+        assert_eq!(processor.get_string("name"), Some("ValUE"));
+
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("verbose", ParameterType::Flag, &["--verbose"]);
+        assert!(processor.try_parse_args(args(&["--Verbose"])).is_err());
+    }
+
+    #[test]
+    fn processor_with_closures_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("verbose", ParameterType::Flag, &["--verbose"]);
+        processor.set_validator("verbose", Box::new(|_| Ok(())));
+        processor.set_on_set("verbose", Box::new(|| ()), false);
+        assert_send_sync(&processor);
+
+        let handle = std::thread::spawn(move || {
+            processor.try_parse_args(args(&["--verbose"])).unwrap();
+            processor.is_flag_set("verbose")
+        });
+        assert!(handle.join().unwrap());
+    }
+
+    fn abbreviation_processor() -> CommandLineProcessor {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("verbose", ParameterType::Flag, &["--verbose"]);
+        processor.add_parameter("version-check", ParameterType::Flag, &["--version-check"]);
+        processor.add_parameter("in", ParameterType::String, &["--in"]);
+        processor.add_parameter("include", ParameterType::String, &["--include"]);
+        processor.set_allow_abbreviations(true);
+        processor
+    }
+
+    #[test]
+    fn unique_abbreviation_resolves_to_its_parameter() {
+        let mut processor = abbreviation_processor();
+        processor.try_parse_args(args(&["--verb", "--inc=src"])).unwrap();
+        assert!(processor.is_flag_set("verbose"));
+        assert_eq!(processor.get_string("include"), Some("src"));
+    }
+
+    #[test]
+    fn ambiguous_abbreviation_lists_candidates() {
+        let mut processor = abbreviation_processor();
+        let errors = processor.try_parse_args(args(&["--ver"])).unwrap_err();
+        match errors[0].kind() {
+            ParseErrorKind::AmbiguousParameter { argument, candidates } => {
+                assert_eq!(argument, "--ver");
+                assert!(candidates.contains(&"--verbose".to_owned()));
+                assert!(candidates.contains(&"--version-check".to_owned()));
+            },
+            kind => panic!("unexpected error {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn exact_alias_wins_over_abbreviation() {
+        let mut processor = abbreviation_processor();
+        processor.try_parse_args(args(&["--in", "a.txt"])).unwrap();
+        assert_eq!(processor.get_string("in"), Some("a.txt"));
+        assert_eq!(processor.get_string("include"), None);
+    }
+
+    #[test]
+    fn empty_abbreviation_matches_nothing() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("name", ParameterType::String, &["--name"]);
+        processor.set_allow_abbreviations(true);
+        assert!(processor.try_parse_args(args(&["--=x"])).is_err());
+        assert_eq!(processor.get_string("name"), None);
+
+        let mut processor = abbreviation_processor();
+        let errors = processor.try_parse_args(args(&["--=x"])).unwrap_err();
+        assert!(matches!(errors[0].kind(), ParseErrorKind::UnknownParameter { .. }), "{:?}", errors);
+    }
+
+    #[test]
+    fn ambiguous_abbreviation_reports_the_argument_as_typed() {
+        let mut processor = abbreviation_processor();
+        processor.set_case_insensitive(true);
+        let errors = processor.try_parse_args(args(&["--VER"])).unwrap_err();
+        assert!(matches!(errors[0].kind(), ParseErrorKind::AmbiguousParameter { argument, .. } if argument == "--VER"));
+    }
+
+    fn verbose_processor() -> CommandLineProcessor {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("verbose", ParameterType::Flag, &["--verbose"]);
+        processor
+    }
+
+    #[test]
+    fn flag_with_inline_value_is_rejected() {
+        let mut processor = verbose_processor();
+        let errors = processor.try_parse_args(args(&["--verbose=true"])).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0].kind(), ParseErrorKind::UnexpectedValue { flag } if flag == "--verbose"));
+        assert_eq!(errors[0].to_string(), "Flag --verbose does not take a value");
+    }
+
+    #[test]
+    fn flag_followed_by_boolean_is_reported_once() {
+        let mut processor = verbose_processor();
+        let errors = processor.try_parse_args(args(&["--verbose", "true"])).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0].kind(), ParseErrorKind::UnexpectedValue { .. }));
+        assert_eq!(errors[0].index(), Some(0));
+        assert!(processor.is_flag_set("verbose"));
+
+        let mut processor = verbose_processor();
+        processor.parse_args(args(&["--verbose", "true"]));
+        assert!(processor.abort_flag());
+    }
+
+    #[test]
+    fn exclusive_group_ignores_env_fallback_values() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("a", ParameterType::UInteger, &["--a"]);
+        processor.add_parameter("b", ParameterType::UInteger, &["--b"]);
+        processor.add_exclusive_group(vec!["a".to_owned(), "b".to_owned()]);
+        processor.set_env_fallback("a", "CMDPRO_TEST_GROUP_A");
+
+        env::set_var("CMDPRO_TEST_GROUP_A", "1");
+        processor.try_parse_args(args(&["--b", "2"])).unwrap();
+        assert_eq!(processor.get_uinteger("a"), Some(1));
+        assert!(processor.validate().is_ok());
+
+        processor.reset_values();
+        processor.try_parse_args(args(&["--a", "3", "--b", "2"])).unwrap();
+        assert_eq!(processor.validate(), Err(vec![ValidationError::Conflict(vec!["a".to_owned(), "b".to_owned()])]));
+    }
+
+    #[test]
+    fn requirement_ignores_env_fallback_values() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("a", ParameterType::UInteger, &["--a"]);
+        processor.add_parameter("b", ParameterType::UInteger, &["--b"]);
+        processor.add_requirement("a", vec!["b".to_owned()]);
+        processor.set_env_fallback("a", "CMDPRO_TEST_REQUIRE_A");
+
+        env::set_var("CMDPRO_TEST_REQUIRE_A", "1");
+        processor.try_parse_args(args(&[])).unwrap();
+        assert!(processor.validate().is_ok());
+
+        processor.reset_values();
+        processor.try_parse_args(args(&["--a", "3"])).unwrap();
+        let errors = processor.validate().unwrap_err();
+        assert_eq!(errors, vec![ValidationError::MissingDependency { parameter: "a".to_owned(), requires: "b".to_owned() }]);
+        assert_eq!(errors[0].to_string(), "Parameter a requires parameter b");
+    }
+
+    #[test]
+    fn was_provided_ignores_defaults() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("given", ParameterType::UInteger, &["--given"]);
+        processor.add_parameter_with_default("defaulted", ParameterType::UInteger, &["--defaulted"], ParameterValue::UInteger(3));
+        processor.add_parameter("unset", ParameterType::UInteger, &["--unset"]);
+        processor.try_parse_args(args(&["--given", "1"])).unwrap();
+        assert!(processor.was_provided("given"));
+        assert!(!processor.was_provided("defaulted"));
+        assert_eq!(processor.get_uinteger("defaulted"), Some(3));
+        assert!(!processor.was_provided("unset"));
+    }
+
+    #[test]
+    fn short_alias_takes_an_attached_value() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("lines", ParameterType::UInteger, &["-n"]);
+        processor.try_parse_args(args(&["-n5"])).unwrap();
+        assert_eq!(processor.get_uinteger("lines"), Some(5));
+
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("name", ParameterType::String, &["-n"]);
+        processor.add_parameter("force", ParameterType::Flag, &["-f"]);
+        processor.try_parse_args(args(&["-nf"])).unwrap();
+        assert_eq!(processor.get_string("name"), Some("f"));
+        assert!(!processor.is_flag_set("force"));
+
+        processor.reset_values();
+        processor.try_parse_args(args(&["-fnx"])).unwrap();
+        assert!(processor.is_flag_set("force"));
+        assert_eq!(processor.get_string("name"), Some("x"));
+    }
+
+    #[test]
+    fn parameters_iterate_in_registration_order() {
+        let names = ["zeta", "alpha", "mid", "beta", "omega"];
+        let mut processor = CommandLineProcessor::new();
+        for name in &names {
+            processor.add_parameter(name, ParameterType::Flag, &[format!("--{}", name)]);
+        }
+        assert_eq!(processor.parameter_names().collect::<Vec<_>>(), names);
+        assert_eq!(processor.iter_parameters().map(|info| info.name).collect::<Vec<_>>(), names);
+
+        let help = processor.generate_help_text();
+        let positions: Vec<usize> = names.iter().map(|name| help.find(&format!("--{}", name)).unwrap()).collect();
+        assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn env_fallback_errors_follow_registration_order() {
+        let mut processor = CommandLineProcessor::new();
+        for name in &["charlie", "alpha", "bravo"] {
+            let variable = format!("CMDPRO_TEST_ORDER_{}", name.to_uppercase());
+            processor.add_parameter(name, ParameterType::UInteger, &[format!("--{}", name)]);
+            processor.set_env_fallback(name, &variable);
+            env::set_var(&variable, "invalid");
+        }
+
+        let errors = processor.try_parse_args(args(&[])).unwrap_err();
+        let variables: Vec<&str> = errors.iter()
+            .map(|error| match error.kind() {
+                ParseErrorKind::InvalidEnvValue { variable, .. } => variable.as_str(),
+                kind => panic!("unexpected error {:?}", kind),
+            })
+            .collect();
+        assert_eq!(variables, ["CMDPRO_TEST_ORDER_CHARLIE", "CMDPRO_TEST_ORDER_ALPHA", "CMDPRO_TEST_ORDER_BRAVO"]);
+    }
+
+    #[test]
+    fn hidden_parameter_parses_but_is_not_in_help() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("verbose", ParameterType::Flag, &["--verbose"]);
+        processor.add_parameter("debug-dump", ParameterType::Flag, &["--debug-dump"]);
+        processor.set_hidden("debug-dump");
+        processor.try_parse_args(args(&["--debug-dump"])).unwrap();
+        assert!(processor.is_flag_set("debug-dump"));
+
+        let help = processor.generate_help_text();
+        assert!(help.contains("--verbose"));
+        assert!(!help.contains("--debug-dump"));
+        assert!(!processor.generate_bash_completion("app").contains("--debug-dump"));
+        assert!(!processor.generate_zsh_completion("app").contains("--debug-dump"));
+    }
+
+    #[test]
+    fn analyze_does_not_read_stdin() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("message", ParameterType::String, &["--message"]);
+        processor.set_allow_stdin("message", true);
+        processor.set_validator("message", Box::new(|_| Err("rejected".to_owned())));
+        let report = processor.analyze(&args(&["--message", "-"]));
+        assert!(report.errors.is_empty());
+        assert_eq!(report.matched, vec![("message".to_owned(), ParameterValue::String("-".to_owned()))]);
+        assert_eq!(processor.get_parameter_value("message"), &ParameterValue::None);
+    }
+
+    #[test]
+    fn parameters_macro_adds_each_parameter() {
+        let mut processor = parameters! {
+            verbose: Flag => ["--verbose", "-v",],
+            count: UInteger => ["--count"],
+            input: Path => [],
+        };
+        assert_eq!(processor.parameter_names().collect::<Vec<_>>(), ["verbose", "count", "input"]);
+
+        processor.try_parse_args(args(&["-v", "--count", "3"])).unwrap();
+        assert!(processor.is_flag_set("verbose"));
+        assert_eq!(processor.get_uinteger("count"), Some(3));
+    }
+
+    /// Builds a processor that uses most parameter types and parsing options, for feeding it arbitrary arguments.
+    fn fuzz_processor(case_insensitive: bool) -> CommandLineProcessor {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("all", ParameterType::Flag, &["--all", "-a"]);
+        processor.add_parameter("verbose", ParameterType::Counter, &["--verbose", "-v"]);
+        processor.add_parameter("color", ParameterType::Flag, &["--color"]);
+        processor.set_negatable("color");
+        processor.add_parameter("size", ParameterType::UInteger, &["--size", "-s"]);
+        processor.set_allow_size_suffix("size", true);
+        processor.add_parameter("threads", ParameterType::UInteger, &["--threads"]);
+        processor.set_range("threads", 1, 64);
+        processor.add_parameter("big", ParameterType::ULong, &["--big"]);
+        processor.add_parameter("offset", ParameterType::Integer, &["--offset"]);
+        processor.add_parameter("ratio", ParameterType::Float, &["--ratio"]);
+        processor.add_parameter("enabled", ParameterType::Bool, &["--enabled"]);
+        processor.add_parameter("name", ParameterType::String, &["--name", "-n"]);
+        processor.add_parameter("out", ParameterType::Path, &["--out"]);
+        processor.set_expand_path("out", UnsetVariablePolicy::Error);
+        processor.add_parameter("files", ParameterType::PathList, &["--files"]);
+        processor.add_parameter("tags", ParameterType::StringList, &["--tags"]);
+        processor.set_delimiter("tags", ',');
+        processor.add_choice_parameter("mode", &["--mode"], vec!["fast".to_owned(), "Safe".to_owned()]);
+        processor.add_union_parameter("limit", &["--limit"], vec![ParameterType::UInteger, ParameterType::Float], vec!["none".to_owned()]);
+        processor.add_nargs_parameter("point", &["--point"], ParameterType::StringList, 2);
+        processor.add_positional("first", ParameterType::Integer);
+        processor.add_positional("rest", ParameterType::StringList);
+        processor.add_subcommand("run").add_parameter("jobs", ParameterType::UInteger, &["--jobs", "-j"]);
+        processor.set_allow_abbreviations(true);
+        processor.set_case_insensitive(case_insensitive);
+        processor
+    }
+
+    /// Returns the next number from a xorshift generator, so the random arguments are the same on every run.
+    fn next_random(state: &mut u64) -> u64 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
+    }
+
+    #[test]
+    fn random_arguments_never_panic() {
+        let fragments = [
+            "--all", "-a", "-v", "-vvv", "-avs", "--verbose", "--color", "--no-color", "--size", "-s", "--threads",
+            "--big", "--offset", "--ratio", "--enabled", "--name", "-n", "--out", "--files", "--tags", "--mode",
+            "--limit", "--point", "--jobs", "run", "--", "-", "=", "--size=", "--=", "-=", "---", "--ver", "--NAME=X",
+            "5", "0", "-42", "-0", "-.5", "1e999", "NaN", "-inf", "10k", "2mi", "5g", "5\u{212A}", "\u{212A}i",
+            "99999999999999999999", "4294967295", "4294967296", "18446744073709551616", "1_000", "_", "k", "ki",
+            "true", "off", "fast", "safe", "none", "~", "~user", "$", "$HOME", "${", "${}", "${CMDPRO_UNSET_VARIABLE}",
+            "a,b,,c", ",", "", " ", "\u{0}", "\u{130}", "\u{df}", "\u{1F600}", "e\u{301}", "@missing-response-file",
+        ];
+
+        let mut state = 0x2545_f491_4f6c_dd1d;
+        for round in 0..4000u32 {
+            let count = next_random(&mut state) % 8;
+            let arguments: Vec<String> = (0..count).map(|_| {
+                let mut argument = fragments[next_random(&mut state) as usize % fragments.len()].to_owned();
+                if next_random(&mut state).is_multiple_of(4) {
+                    argument.push_str(fragments[next_random(&mut state) as usize % fragments.len()]);
+                }
+                if next_random(&mut state).is_multiple_of(8) {
+                    argument.extend(std::char::from_u32(next_random(&mut state) as u32 % 0x3000));
+                }
+                argument
+            }).collect();
+
+            let mut processor = fuzz_processor(round.is_multiple_of(2));
+            processor.set_response_files(round.is_multiple_of(3));
+            let _ = processor.try_parse_args(arguments.clone());
+            let _ = processor.analyze(&arguments);
+            let _ = processor.try_parse_str(&arguments.join(" "));
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_arguments_never_panic() {
+        use std::os::unix::ffi::OsStringExt;
+
+        let invalid = [vec![0xff], vec![b'-', 0xfe], vec![b'-', b'-', 0xc3], vec![b'-', b'a', 0x80], vec![b'-', b's', 0xe2, 0x84]];
+        for bytes in &invalid {
+            for prefix in &["--name", "--size", "-s", "--out", "--files", "run"] {
+                let mut processor = fuzz_processor(false);
+                let _ = processor.try_parse_args_os(vec![OsString::from(prefix), OsString::from_vec(bytes.clone())]);
+                let _ = processor.try_parse_args_os(vec![OsString::from_vec(bytes.clone()), OsString::from(prefix)]);
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn path_keeps_bytes_that_are_not_utf8() {
+        use std::os::unix::ffi::OsStringExt;
+
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("out", ParameterType::Path, &["--out"]);
+        processor.add_parameter("name", ParameterType::String, &["--name"]);
+        let path = OsString::from_vec(vec![b'a', 0xff]);
+        processor.try_parse_args_os(vec![OsString::from("--out"), path.clone(), OsString::from("--name"), path.clone()]).unwrap();
+        assert_eq!(processor.get_path("out"), Some(Path::new(&path)));
+        assert_eq!(processor.get_string("name"), Some("a\u{FFFD}"));
+    }
+
+    #[test]
+    fn parse_trace_pairs_arguments_with_parameters() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("verbose", ParameterType::Flag, &["-v"]);
+        processor.add_parameter("quiet", ParameterType::Flag, &["-q"]);
+        processor.add_parameter("name", ParameterType::String, &["--name"]);
+        processor.set_mode(ParseMode::Permissive);
+        processor.try_parse_args(args(&["-vq", "--name", "demo", "--other", "-Wall"])).unwrap();
+
+        let trace: Vec<(&str, &str)> = processor.parse_trace().iter().map(|(argument, name)| (argument.as_str(), name.as_str())).collect();
+        assert_eq!(trace, vec![("-vq", "verbose"), ("-vq", "quiet"), ("--name", "name"), ("demo", "name"), ("--other", ""), ("-Wall", "")]);
+    }
+
+    #[test]
+    fn uinteger_accepts_underscores_and_size_suffixes() {
+        for &(text, expected) in &[("1_000", 1_000), ("10k", 10_000), ("2mi", 2 << 20), ("4g", 4_000_000_000)] {
+            let mut processor = CommandLineProcessor::new();
+            processor.add_parameter("size", ParameterType::UInteger, &["--size"]);
+            processor.set_allow_size_suffix("size", true);
+            processor.try_parse_args(args(&["--size", text])).unwrap();
+            assert_eq!(processor.get_uinteger("size"), Some(expected), "{}", text);
+        }
+
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("size", ParameterType::UInteger, &["--size"]);
+        processor.set_allow_size_suffix("size", true);
+        let errors = processor.try_parse_args(args(&["--size", "5g"])).unwrap_err();
+        assert!(matches!(errors[0].kind(), ParseErrorKind::ValueTooLarge { max, .. } if *max == u64::from(u32::MAX)));
+    }
+
+    #[test]
+    fn size_suffixes_are_off_by_default() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("size", ParameterType::UInteger, &["--size"]);
+        assert!(processor.try_parse_args(args(&["--size", "10k"])).is_err());
+    }
+
+    #[test]
+    fn size_suffix_is_matched_on_ascii_characters_only() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("size", ParameterType::UInteger, &["--size"]);
+        processor.set_allow_size_suffix("size", true);
+        let errors = processor.try_parse_args(args(&["--size", "5\u{212A}"])).unwrap_err();
+        assert!(matches!(errors[0].kind(), ParseErrorKind::InvalidUInteger { .. }));
+
+        processor.reset_values();
+        processor.try_parse_args(args(&["--size", "5K"])).unwrap();
+        assert_eq!(processor.get_uinteger("size"), Some(5_000));
+    }
+
+    #[test]
+    fn help_descriptions_wrap_to_the_help_width() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter_with_description("all", ParameterType::Flag, &["--all"], "Include every file in the listing output");
+        processor.set_help_width(33);
+        let help = processor.generate_help_text();
+        assert!(help.contains("    --all    Include every file\n             in the listing\n             output"), "{}", help);
+    }
+
+    fn temp_file(name: &str, contents: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!("cmdpro-{}-{}", std::process::id(), name));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn response_file_arguments_are_spliced_in() {
+        let inner = temp_file("inner.args", "-v");
+        let outer = temp_file("outer.args", &format!("--name 'two words' @{}", inner.display()));
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("name", ParameterType::String, &["--name"]);
+        processor.add_parameter("verbose", ParameterType::Flag, &["-v"]);
+        processor.set_response_files(true);
+        processor.try_parse_args(vec![format!("@{}", outer.display()), "--".to_owned(), "@kept".to_owned()]).unwrap();
+        assert_eq!(processor.get_string("name"), Some("two words"));
+        assert!(processor.is_flag_set("verbose"));
+        assert_eq!(processor.get_trailing_args(), &["@kept".to_owned()]);
+
+        let errors = processor.try_parse_args(args(&["@cmdpro-missing-response-file"])).unwrap_err();
+        assert!(matches!(errors[0].kind(), ParseErrorKind::ResponseFileFailed { .. }));
+        fs::remove_file(inner).unwrap();
+        fs::remove_file(outer).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "Parameter name has an empty alias")]
+    fn empty_alias_panics() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("name", ParameterType::String, &["--name", ""]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Parameter name can't be empty")]
+    fn empty_parameter_name_panics() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("", ParameterType::String, &["--name"]);
+    }
+
+    #[test]
+    fn alias_without_dash_adds_a_warning() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("name", ParameterType::String, &["name"]);
+        assert_eq!(processor.warnings(), &["Alias name of parameter name does not start with -".to_owned()]);
+    }
+
+    #[test]
+    fn spec_default_converts_integers_to_the_declared_type() {
+        let mut processor = CommandLineProcessor::new();
+        let count = processor.add(ParameterSpec::builder("count").aliases(["--count", "-c"]).uinteger().default(10).required());
+        processor.add(ParameterSpec::builder("ratio").float().default(2));
+        processor.add(ParameterSpec::builder("offset").integer().default(-3));
+        assert_eq!(processor.get_uinteger(&count), Some(10));
+        assert_eq!(processor.get_float("ratio"), Some(2.0));
+        assert_eq!(processor.get_parameter_value("offset"), &ParameterValue::Integer(-3));
+    }
+
+    #[test]
+    #[should_panic(expected = "Default value for parameter count does not match its type")]
+    fn spec_default_rejects_negative_unsigned_default() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add(ParameterSpec::builder("count").uinteger().default(-1));
+    }
+
+    #[test]
+    fn deprecation_warnings_are_replaced_on_each_parse() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("old", ParameterType::Flag, &["--old", "old"]);
+        processor.set_deprecated("old", "use --new instead");
+        let alias_warning = "Alias old of parameter old does not start with -";
+
+        for _ in 0..3 {
+            processor.try_parse_args(args(&["--old"])).unwrap();
+            assert!(processor.is_flag_set("old"));
+            assert_eq!(processor.warnings(), [alias_warning, "Parameter --old is deprecated: use --new instead"]);
+        }
+
+        processor.reset_values();
+        assert_eq!(processor.warnings(), [alias_warning]);
+        processor.try_parse_args(args(&[])).unwrap();
+        assert_eq!(processor.warnings(), [alias_warning]);
+    }
+
+    #[test]
+    fn merge_skips_parameters_that_collide() {
+        let mut global = CommandLineProcessor::new();
+        global.add_parameter("verbose", ParameterType::Flag, &["--verbose", "-v"]);
+        global.add_parameter("quiet", ParameterType::Flag, &["--quiet", "-q"]);
+
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("version", ParameterType::Flag, &["-v"]);
+        assert_eq!(processor.merge(&global), Err(vec!["verbose".to_owned()]));
+        processor.try_parse_args(args(&["-q"])).unwrap();
+        assert!(processor.is_flag_set("quiet"));
+        assert!(!processor.has_parameter("verbose"));
+    }
+
+    #[test]
+    fn auto_short_aliases_report_conflicts() {
+        let mut processor = CommandLineProcessor::new();
+        processor.set_auto_short_aliases(true);
+        processor.add_parameter("verbose", ParameterType::Flag, &["--verbose"]);
+        processor.add_parameter("verify", ParameterType::Flag, &["--verify"]);
+        processor.add_parameter("host", ParameterType::String, &["--host"]);
+        assert_eq!(processor.warnings(), &[
+            "Short alias -v for parameter verify is already used by parameter verbose".to_owned(),
+            "Short alias -h for parameter host is already used by the help or version text".to_owned(),
+        ]);
+
+        processor.try_parse_args(args(&["-v"])).unwrap();
+        assert!(processor.is_flag_set("verbose"));
+        assert!(!processor.is_flag_set("verify"));
+    }
+
+    #[test]
+    fn path_constraints_check_absolute_and_relative() {
+        let absolute = if cfg!(windows) { "C:\\srv" } else { "/srv" };
+        for &(relative_required, text, accepted) in &[(false, absolute, true), (false, "srv", false), (true, "srv", true), (true, absolute, false)] {
+            let mut processor = CommandLineProcessor::new();
+            processor.add_parameter("dir", ParameterType::Path, &["--dir"]);
+            if relative_required {
+                processor.set_require_relative("dir");
+            } else {
+                processor.set_require_absolute("dir");
+            }
+
+            match processor.try_parse_args(args(&["--dir", text])) {
+                Ok(()) => assert!(accepted, "{} was accepted", text),
+                Err(errors) => {
+                    assert!(!accepted, "{} was rejected", text);
+                    match errors[0].kind() {
+                        ParseErrorKind::PathNotAbsolute { parameter, path } | ParseErrorKind::PathNotRelative { parameter, path } => {
+                            assert_eq!(parameter, "dir");
+                            assert_eq!(path, Path::new(text));
+                        },
+                        kind => panic!("unexpected error {:?}", kind),
+                    }
+                },
+            }
+        }
+    }
+
+    fn secret_processor() -> CommandLineProcessor {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter_with_default("password", ParameterType::String, &["--password", "-p"], ParameterValue::String("fallback-secret".to_owned()));
+        processor.add_parameter("verbose", ParameterType::Flag, &["-v"]);
+        processor.set_secret("password");
+        processor
+    }
+
+    #[test]
+    fn secret_value_is_redacted() {
+        let mut processor = secret_processor();
+        processor.try_parse_args(args(&["--password", "hunter2", "--password=hunter3", "-vphunter4"])).unwrap();
+        assert_eq!(processor.get_string("password"), Some("hunter4"));
+
+        let trace = format!("{:?}", processor.parse_trace());
+        assert!(trace.contains("***"));
+        let report = format!("{:?}", processor.analyze(&args(&["--password", "hunter2"])));
+        assert!(report.contains("***"));
+        let state = processor.describe_state();
+        assert!(state.contains("password: ***"));
+        let snapshot = processor.values_snapshot();
+        assert_eq!(snapshot["password"], ParameterValue::String("***".to_owned()));
+        assert_eq!(snapshot["verbose"], ParameterValue::Flag);
+
+        for text in &[trace, report, state, processor.generate_help_text()] {
+            for secret in &["hunter2", "hunter3", "hunter4", "fallback-secret"] {
+                assert!(!text.contains(secret), "{} appears in {}", secret, text);
+            }
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn secret_value_is_redacted_in_json() {
+        let mut processor = secret_processor();
+        processor.try_parse_args(args(&["--password", "hunter2"])).unwrap();
+        let json = processor.to_json();
+        assert!(json.contains("\"password\":\"***\""), "{}", json);
+        assert!(!json.contains("hunter2"));
+    }
+
+    #[test]
+    fn usage_line_brackets_optional_parameters() {
+        let mut processor = CommandLineProcessor::new();
+        processor.set_program_name("tool");
+        processor.add_parameter("count", ParameterType::UInteger, &["--count", "-c"]);
+        processor.add_parameter("verbose", ParameterType::Flag, &["--verbose"]);
+        processor.add_parameter("name", ParameterType::String, &["--name"]);
+        processor.set_required("name");
+        processor.add_positional("files", ParameterType::PathList);
+        assert_eq!(processor.generate_usage_line(), "Usage: tool [--count <uint>] [--verbose] --name <string> [files...]");
+    }
+
+    #[test]
+    fn value_name_replaces_the_placeholder() {
+        let mut processor = CommandLineProcessor::new();
+        processor.set_program_name("tool");
+        processor.add_parameter("port", ParameterType::UInteger, &["--port"]);
+        processor.set_value_name("port", "PORT");
+        assert_eq!(processor.generate_usage_line(), "Usage: tool [--port <PORT>]");
+        assert!(processor.generate_help_text().contains("--port <PORT>"));
+    }
+
+    fn expanded_path(policy: UnsetVariablePolicy, text: &str) -> Result<PathBuf, Vec<ParseError>> {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("log", ParameterType::Path, &["--log"]);
+        processor.set_expand_path("log", policy);
+        processor.try_parse_args(args(&["--log", text]))?;
+        Ok(processor.get_path("log").unwrap().to_path_buf())
+    }
+
+    #[test]
+    fn path_expansion_uses_home_and_variables() {
+        let home = env::var("HOME").unwrap_or_else(|_| {
+            env::set_var("HOME", "/home/cmdpro");
+            "/home/cmdpro".to_owned()
+        });
+        env::set_var("CMDPRO_TEST_DATA", "data");
+        assert_eq!(expanded_path(UnsetVariablePolicy::Error, "~/logs/app.log").unwrap(), PathBuf::from(format!("{}/logs/app.log", home)));
+        assert_eq!(expanded_path(UnsetVariablePolicy::Error, "$HOME/$CMDPRO_TEST_DATA").unwrap(), PathBuf::from(format!("{}/data", home)));
+        assert_eq!(expanded_path(UnsetVariablePolicy::Error, "${CMDPRO_TEST_DATA}x").unwrap(), PathBuf::from("datax"));
+        assert_eq!(expanded_path(UnsetVariablePolicy::Error, "~user/a").unwrap(), PathBuf::from("~user/a"));
+    }
+
+    #[test]
+    fn path_expansion_follows_the_unset_variable_policy() {
+        let errors = expanded_path(UnsetVariablePolicy::Error, "$CMDPRO_TEST_UNDEFINED/app.log").unwrap_err();
+        match errors[0].kind() {
+            ParseErrorKind::UnsetVariable { parameter, variable } => assert_eq!((parameter.as_str(), variable.as_str()), ("log", "CMDPRO_TEST_UNDEFINED")),
+            kind => panic!("unexpected error {:?}", kind),
+        }
+        assert_eq!(expanded_path(UnsetVariablePolicy::Empty, "$CMDPRO_TEST_UNDEFINED/app.log").unwrap(), PathBuf::from("/app.log"));
+    }
+
+    #[test]
+    fn param_key_works_with_the_getters() {
+        let mut processor = CommandLineProcessor::new();
+        let verbose = processor.add_parameter("verbose", ParameterType::Flag, &["-v"]);
+        let name = processor.add_parameter_with_description("name", ParameterType::String, &["--name"], "Name to greet");
+        processor.try_parse_args(args(&["-v", "--name", "demo"])).unwrap();
+        assert_eq!(verbose.name(), "verbose");
+        assert!(processor.is_flag_set(&verbose));
+        assert!(processor.was_provided(&verbose));
+        assert_eq!(processor.get_string(&name), Some("demo"));
+    }
+
+    #[test]
+    fn no_args_provided_is_set_for_an_empty_command_line() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("verbose", ParameterType::Flag, &["-v"]);
+        processor.try_parse_args(args(&[])).unwrap();
+        assert!(processor.no_args_provided());
+
+        processor.reset_values();
+        processor.try_parse_args(args(&["-v"])).unwrap();
+        assert!(!processor.no_args_provided());
+    }
+
+    #[test]
+    fn union_parameter_stores_the_first_matching_type() {
+        for &(text, ref expected) in &[
+            ("30", ParameterValue::UInteger(30)),
+            ("2.5", ParameterValue::Float(2.5)),
+            ("infinite", ParameterValue::String("infinite".to_owned())),
+        ] {
+            let mut processor = CommandLineProcessor::new();
+            processor.add_union_parameter("timeout", &["--timeout"], vec![ParameterType::UInteger, ParameterType::Float], vec!["infinite".to_owned()]);
+            processor.try_parse_args(args(&["--timeout", text])).unwrap();
+            assert_eq!(processor.get_parameter_value("timeout"), expected);
+        }
+
+        let mut processor = CommandLineProcessor::new();
+        processor.add_union_parameter("timeout", &["--timeout"], vec![ParameterType::UInteger, ParameterType::Float], vec!["infinite".to_owned()]);
+        let errors = processor.try_parse_args(args(&["--timeout", "forever"])).unwrap_err();
+        assert!(matches!(errors[0].kind(), ParseErrorKind::NoMatchingType { value, .. } if value == "forever"));
+    }
+
+    #[test]
+    fn every_error_is_reported() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("count", ParameterType::UInteger, &["--count"]);
+        processor.add_parameter("ratio", ParameterType::Float, &["--ratio"]);
+        let errors = processor.try_parse_args(args(&["--count", "abc", "--ratio", "x", "--count", "2"])).unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert!(matches!(errors[0].kind(), ParseErrorKind::InvalidUInteger { parameter, .. } if parameter == "count"));
+        assert_eq!(errors[0].index(), Some(1));
+        assert!(matches!(errors[1].kind(), ParseErrorKind::InvalidFloat { parameter, .. } if parameter == "ratio"));
+        assert_eq!(errors[1].index(), Some(3));
+        assert_eq!(processor.get_uinteger("count"), Some(2));
+    }
+
+    #[test]
+    fn help_sections_follow_the_parameters() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("verbose", ParameterType::Flag, &["--verbose"]);
+        processor.add_help_section("Examples", "app --verbose\n\napp");
+        let help = processor.generate_help_text();
+        assert!(help.ends_with("\n\nExamples:\n    app --verbose\n\n    app"), "{}", help);
+    }
+
+    #[test]
+    fn on_set_function_runs_and_can_stop_parsing() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("list", ParameterType::Flag, &["--list", "-l"]);
+        processor.add_parameter("count", ParameterType::UInteger, &["--count"]);
+        processor.set_on_set("list", Box::new(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        }), true);
+
+        processor.try_parse_args(args(&["--list", "--count", "5"])).unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(processor.abort_flag());
+        assert_eq!(processor.get_uinteger("count"), None);
+
+        processor.analyze(&args(&["-l"]));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn parameter_type_is_returned_without_parsing() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("count", ParameterType::UInteger, &["--count"]);
+        assert_eq!(processor.get_parameter_type("count"), Some(&ParameterType::UInteger));
+        assert_eq!(processor.get_parameter_type("missing"), None);
+    }
+
+    #[test]
+    fn argument_after_flag_is_parsed_on_its_own() {
+        let mut processor = verbose_processor();
+        processor.add_positional("target", ParameterType::String);
+        processor.try_parse_args(args(&["--verbose", "foo"])).unwrap();
+        assert!(processor.is_flag_set("verbose"));
+        assert_eq!(processor.get_positional("target"), &ParameterValue::String("foo".to_owned()));
+
+        let mut processor = verbose_processor();
+        let errors = processor.try_parse_args(args(&["--verbose", "foo"])).unwrap_err();
+        assert!(matches!(errors[0].kind(), ParseErrorKind::UnexpectedPositional { argument } if argument == "foo"));
+        assert_eq!(errors[0].index(), Some(1));
+    }
+
+    #[test]
+    fn command_line_is_split_at_unquoted_whitespace() {
+        assert_eq!(split_command_line(r#"--name 'a b'  "c \"d\" \\" e\ f g\"#), ["--name", "a b", "c \"d\" \\", "e f", "g\\"]);
+        assert_eq!(split_command_line("''"), [""]);
+        assert!(split_command_line("  ").is_empty());
+
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("name", ParameterType::String, &["--name"]);
+        processor.try_parse_str("--name 'Jane Doe'").unwrap();
+        assert_eq!(processor.get_string("name"), Some("Jane Doe"));
+    }
+
+    #[test]
+    fn uinteger_overflow_reports_the_maximum() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("count", ParameterType::UInteger, &["--count"]);
+        processor.try_parse_args(args(&["--count", "4294967295"])).unwrap();
+        assert_eq!(processor.get_uinteger("count"), Some(u32::MAX));
+
+        processor.reset_values();
+        let errors = processor.try_parse_args(args(&["--count", "4294967296"])).unwrap_err();
+        assert!(matches!(errors[0].kind(), ParseErrorKind::ValueTooLarge { max: 4294967295, .. }));
+        assert_eq!(errors[0].to_string(), "Value 4294967296 for parameter count is too large, the maximum is 4294967295");
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml_defaults_are_coerced_to_each_type() {
+        let path = temp_file("defaults.toml", r#"
+            [defaults]
+            verbose = true
+            count = 3
+            big = 5000000000
+            offset = -2
+            ratio = 0.5
+            whole = 2
+            enabled = false
+            name = "server"
+            out = "logs/app.log"
+            files = ["a.txt", "b.txt"]
+            tags = "one"
+            level = 2
+        "#);
+
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("verbose", ParameterType::Flag, &["--verbose"]);
+        processor.add_parameter("count", ParameterType::UInteger, &["--count"]);
+        processor.add_parameter("big", ParameterType::ULong, &["--big"]);
+        processor.add_parameter("offset", ParameterType::Integer, &["--offset"]);
+        processor.add_parameter("ratio", ParameterType::Float, &["--ratio"]);
+        processor.add_parameter("whole", ParameterType::Float, &["--whole"]);
+        processor.add_parameter("enabled", ParameterType::Bool, &["--enabled"]);
+        processor.add_parameter("name", ParameterType::String, &["--name"]);
+        processor.add_parameter("out", ParameterType::Path, &["--out"]);
+        processor.add_parameter("files", ParameterType::PathList, &["--files"]);
+        processor.add_parameter("tags", ParameterType::StringList, &["--tags"]);
+        processor.add_parameter("level", ParameterType::Counter, &["-l"]);
+        processor.load_defaults_from_toml(&path).unwrap();
+        processor.try_parse_args(args(&["--count", "7"])).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert!(processor.is_flag_set("verbose"));
+        assert_eq!(processor.get_uinteger("count"), Some(7));
+        assert_eq!(processor.get_ulong("big"), Some(5_000_000_000));
+        assert_eq!(processor.get_parameter_value("offset"), &ParameterValue::Integer(-2));
+        assert_eq!(processor.get_float("ratio"), Some(0.5));
+        assert_eq!(processor.get_float("whole"), Some(2.0));
+        assert_eq!(processor.get_bool("enabled"), Some(false));
+        assert_eq!(processor.get_string("name"), Some("server"));
+        assert_eq!(processor.get_path("out"), Some(Path::new("logs/app.log")));
+        assert_eq!(processor.get_path_list("files"), &[PathBuf::from("a.txt"), PathBuf::from("b.txt")]);
+        assert_eq!(processor.get_string_list("tags"), &["one".to_owned()]);
+        assert_eq!(processor.get_count("level"), 2);
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml_type_mismatch_is_an_error() {
+        let path = temp_file("mismatch.toml", "[defaults]\ncount = \"many\"\n");
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("count", ParameterType::UInteger, &["--count"]);
+        let result = processor.load_defaults_from_toml(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(result, Err(ConfigError::TypeMismatch { ref key, expected: ParameterType::UInteger }) if key == "count"));
+    }
+
+    #[test]
+    fn state_lists_each_value_with_its_source() {
+        let path = temp_file("state.conf", "name = from-file\n");
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("count", ParameterType::UInteger, &["--count"]);
+        processor.add_parameter("verbose", ParameterType::Flag, &["--verbose"]);
+        processor.add_parameter("name", ParameterType::String, &["--name"]);
+        processor.add_parameter_with_default("level", ParameterType::String, &["--level"], ParameterValue::String("info".to_owned()));
+        processor.add_positional("files", ParameterType::StringList);
+        processor.load_from_file(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        processor.try_parse_args(args(&["--count", "5", "a", "b"])).unwrap();
+
+        assert_eq!(processor.describe_state(), "count: 5 (command line)\nverbose: <unset>\nname: from-file (config file)\n\
+            level: info (default)\nfiles: a, b (command line)");
+    }
+
+    #[test]
+    fn help_lists_short_aliases_first() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("verbose", ParameterType::Flag, &["--verbose", "-v"]);
+        assert!(processor.generate_help_text().contains("-v, --verbose"));
+    }
+
+    fn config_processor() -> CommandLineProcessor {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("config", ParameterType::Path, &["--config"]);
+        processor.set_config_parameter("config");
+        processor.add_parameter("count", ParameterType::UInteger, &["--count"]);
+        processor.add_parameter("name", ParameterType::String, &["--name"]);
+        processor.add_parameter("tags", ParameterType::StringList, &["--tags"]);
+        processor.add_subcommand("run").add_parameter("config", ParameterType::Path, &["--config"]);
+        processor
+    }
+
+    #[test]
+    fn config_parameter_is_loaded_before_other_arguments() {
+        let path = temp_file("app.conf", "count = 1\nname = from-file\ntags = a\nextra = 2\n");
+        let config = path.to_string_lossy().into_owned();
+
+        let mut processor = config_processor();
+        processor.try_parse_args(args(&["--count", "5", "--config", &config])).unwrap();
+        assert_eq!(processor.get_uinteger("count"), Some(5));
+        assert_eq!(processor.get_string("name"), Some("from-file"));
+        assert_eq!(processor.describe_state().lines().nth(2), Some("name: from-file (config file)"));
+
+        for _ in 0..3 {
+            processor.reset_values();
+            processor.try_parse_args(args(&[&format!("--config={}", config)])).unwrap();
+        }
+        let _ = processor.analyze(&args(&["--config", &config]));
+        assert_eq!(processor.get_string_list("tags"), &["a".to_owned()]);
+        assert_eq!(processor.warnings().len(), 1);
+
+        processor.reset_values();
+        processor.try_parse_args(args(&[])).unwrap();
+        assert_eq!(processor.get_string("name"), None);
+        assert!(processor.warnings().is_empty());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn config_pre_pass_skips_values_and_subcommands() {
+        let path = temp_file("skipped.conf", "name = from-file\n");
+        let config = path.to_string_lossy().into_owned();
+
+        let mut processor = config_processor();
+        processor.try_parse_args(args(&["--name", "--config", "--count", "2"])).unwrap();
+        assert_eq!(processor.get_string("name"), Some("--config"));
+        assert_eq!(processor.get_uinteger("count"), Some(2));
+
+        let mut processor = config_processor();
+        processor.try_parse_args(args(&["run", "--config", &config])).unwrap();
+        assert_eq!(processor.get_string("name"), None);
+        assert_eq!(processor.get_subcommand("run").unwrap().get_path("config"), Some(path.as_path()));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn missing_config_file_is_an_error() {
+        let mut processor = config_processor();
+        let errors = processor.try_parse_args(args(&["--config", "cmdpro-missing.conf"])).unwrap_err();
+        assert!(matches!(errors[0].kind(), ParseErrorKind::ConfigFileFailed { path, .. } if path == Path::new("cmdpro-missing.conf")));
+    }
+
+    #[test]
+    fn require_getters_return_the_value_or_missing_param() {
+        let mut processor = CommandLineProcessor::new();
+        processor.add_parameter("offset", ParameterType::Integer, &["--offset"]);
+        processor.add_parameter("name", ParameterType::String, &["--name"]);
+        processor.try_parse_args(args(&["--offset", "-7"])).unwrap();
+        assert_eq!(processor.get_integer("offset"), Some(-7));
+        assert_eq!(processor.require_integer("offset"), Ok(-7));
+
+        let error = processor.require_string("name").unwrap_err();
+        assert_eq!(error, MissingParam { parameter: "name".to_owned(), expected: ParameterType::String });
+        assert_eq!(error.to_string(), "Parameter name has no String value");
+        assert!(processor.require_integer("name").is_err());
+    }
+}